@@ -0,0 +1,148 @@
+//! Embeds the `content/` directory into the binary as the built-in filesystem.
+//! `content.manifest` lists the entries that can't be plain files and directories,
+//! and the permissions that aren't the usual ones.
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[path = "src/content/mime.rs"]
+mod mime;
+
+const CONTENT_DIR: &str = "content";
+const MANIFEST: &str = "content.manifest";
+/// The owner and group of the entries the manifest doesn't give one
+const DEFAULT_OWNER: &str = "root";
+
+enum Entry {
+    Directory,
+    File(PathBuf),
+    Link(String),
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", CONTENT_DIR);
+    println!("cargo:rerun-if-changed={}", MANIFEST);
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut entries = BTreeMap::new();
+    collect(&root.join(CONTENT_DIR), "", &mut entries);
+
+    let manifest = fs::read_to_string(root.join(MANIFEST)).unwrap_or_default();
+    let manifest_modified = modified(&root.join(MANIFEST));
+    let mut modes = BTreeMap::new();
+    let mut owners = vec![];
+    for (idx, line) in manifest.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fail = |reason: &str| -> ! {
+            panic!("{}:{}: {}: {}", MANIFEST, idx + 1, reason, line);
+        };
+        match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["link", path, target] => {
+                if entries.contains_key(*path) {
+                    fail("the link is also a file in content/");
+                }
+                entries.insert(
+                    path.to_string(),
+                    (Entry::Link(target.to_string()), manifest_modified),
+                );
+            }
+            ["mode", path, mode] => {
+                if !entries.contains_key(*path) {
+                    fail("no such entry");
+                }
+                let Ok(mode) = u32::from_str_radix(mode, 8) else {
+                    fail("the mode is not an octal number");
+                };
+                modes.insert(path.to_string(), mode & 0o777);
+            }
+            ["owner", path, owner] => {
+                if !entries.contains_key(*path) {
+                    fail("no such entry");
+                }
+                let (user, group) = owner.split_once(':').unwrap_or((owner, owner));
+                owners.push((path.to_string(), user.to_string(), group.to_string()));
+            }
+            _ => fail("expected `link PATH TARGET`, `mode PATH MODE` or `owner PATH USER[:GROUP]`"),
+        }
+    }
+
+    let mut code = String::from("// Generated by build.rs from content/ and content.manifest\n\n");
+    code += "pub(crate) static CONTENT: &[(&str, Metadata, Content)] = &[\n";
+    for (path, (entry, modified)) in &entries {
+        // The last rule for the entry or one of its parents wins
+        let (owner, group) = owners
+            .iter()
+            .rev()
+            .find(|(prefix, _, _)| path == prefix || path.starts_with(&format!("{}/", prefix)))
+            .map_or((DEFAULT_OWNER, DEFAULT_OWNER), |(_, owner, group)| {
+                (owner.as_str(), group.as_str())
+            });
+        let (mode, mime, entry) = match entry {
+            Entry::Directory => (0o755, "inode/directory", "Content::Directory".to_string()),
+            Entry::File(file) => {
+                let contents = fs::read_to_string(file).expect("Reading content/");
+                (
+                    0o644,
+                    mime::mime_type(path, &contents),
+                    format!("Content::File(include_str!({:?}))", file),
+                )
+            }
+            Entry::Link(target) => (
+                0o777,
+                "inode/symlink",
+                format!("Content::Link({:?})", target),
+            ),
+        };
+        let mode = modes.get(path).copied().unwrap_or(mode);
+        code += &format!(
+            "    ({:?}, Metadata {{ mode: {:#o}, modified: {}, owner: {:?}, group: {:?}, mime: {:?} }}, {}),\n",
+            path, mode, modified, owner, group, mime, entry
+        );
+    }
+    code += "];\n";
+
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("content.rs");
+    fs::write(out, code).expect("Writing the generated content");
+}
+
+/// The last modification of a file, in seconds since the Unix epoch
+fn modified(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .unwrap_or_else(|_| SystemTime::now())
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+/// Add the entries below `dir` to `entries`, by their path relative to `content/`,
+/// with their last modification
+fn collect(dir: &Path, prefix: &str, entries: &mut BTreeMap<String, (Entry, u64)>) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+    for dir_entry in read_dir {
+        let dir_entry = dir_entry.expect("Reading content/");
+        let name = dir_entry.file_name().to_string_lossy().to_string();
+        let path = format!("{}{}", prefix, name);
+        let file_type = dir_entry.file_type().expect("Reading content/");
+        if file_type.is_dir() {
+            entries.insert(
+                path.clone(),
+                (Entry::Directory, modified(&dir_entry.path())),
+            );
+            collect(&dir_entry.path(), &format!("{}/", path), entries);
+        } else if fs::read_to_string(dir_entry.path()).is_ok() {
+            let modified = modified(&dir_entry.path());
+            entries.insert(path, (Entry::File(dir_entry.path()), modified));
+        } else {
+            println!("cargo:warning=skipping {}: not a UTF-8 text file", path);
+        }
+    }
+}
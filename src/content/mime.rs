@@ -0,0 +1,40 @@
+//! Guessing the MIME type of a file. Shared with `build.rs`, which fills it in
+//! for the built-in content.
+
+/// The MIME type of a file from its name, or from its contents when the
+/// extension is unknown
+pub(crate) fn mime_type(name: &str, contents: &str) -> &'static str {
+    if contents.is_empty() {
+        return "inode/x-empty";
+    }
+    let extension = name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase());
+    match extension.as_deref() {
+        Some("md" | "markdown") => return "text/markdown",
+        Some("txt") => return "text/plain",
+        Some("rs") => return "text/x-rust",
+        Some("toml") => return "application/toml",
+        Some("json") => return "application/json",
+        Some("yaml" | "yml") => return "application/yaml",
+        Some("html" | "htm") => return "text/html",
+        Some("css") => return "text/css",
+        Some("js") => return "text/javascript",
+        Some("sh") => return "text/x-shellscript",
+        Some("py") => return "text/x-python",
+        Some("csv") => return "text/csv",
+        Some("xml") => return "application/xml",
+        Some("svg") => return "image/svg+xml",
+        _ => {}
+    }
+    let start = contents.trim_start();
+    if start.starts_with("#!") {
+        "text/x-shellscript"
+    } else if start.to_lowercase().starts_with("<!doctype html") || start.starts_with("<html") {
+        "text/html"
+    } else if start.starts_with("<?xml") {
+        "application/xml"
+    } else {
+        "text/plain"
+    }
+}
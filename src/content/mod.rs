@@ -0,0 +1,24 @@
+mod mime;
+
+pub(crate) use mime::mime_type;
+
+/// An entry of the `content/` directory, embedded by `build.rs`. Paths are relative to `/`.
+pub(crate) enum Content {
+    Directory,
+    File(&'static str),
+    /// A link to the path, from `content.manifest`
+    Link(&'static str),
+}
+
+/// The metadata of an entry of `content/`, collected by `build.rs`
+pub(crate) struct Metadata {
+    /// The permission bits, from `content.manifest` or the usual ones for the type
+    pub(crate) mode: u32,
+    /// The last modification of the file in `content/`, in seconds since the Unix epoch
+    pub(crate) modified: u64,
+    pub(crate) owner: &'static str,
+    pub(crate) group: &'static str,
+    pub(crate) mime: &'static str,
+}
+
+include!(concat!(env!("OUT_DIR"), "/content.rs"));
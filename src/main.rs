@@ -1,10 +1,15 @@
 #![allow(non_snake_case)]
 
+use std::collections::BTreeMap;
+
 use dioxus::prelude::*;
 use gloo_storage::Storage;
 use log::LevelFilter;
 use serde::{Deserialize, Serialize};
-use terminal::{buffer::TerminalBuffer, filesystem::Filesystem, history::History};
+use terminal::{
+    alias::Aliases, buffer::TerminalBuffer, commands::su::Su, environment::Environment,
+    filesystem::Filesystem, history::History, overlay::Overlay,
+};
 use ui::themes::TerminalTheme;
 use web_sys::js_sys::Function;
 
@@ -13,7 +18,7 @@ use crate::ui::{
     themes::{LATTE, MOCHA},
 };
 
-pub(crate) mod projects;
+pub(crate) mod content;
 pub(crate) mod terminal;
 pub(crate) mod ui;
 
@@ -28,9 +33,13 @@ fn main() {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct LocalStorageSettings {
     theme: String,
     history: Vec<String>,
+    env: BTreeMap<String, String>,
+    aliases: BTreeMap<String, String>,
+    filesystem: Overlay,
 }
 
 impl Default for LocalStorageSettings {
@@ -38,6 +47,9 @@ impl Default for LocalStorageSettings {
         Self {
             theme: "mocha".to_string(),
             history: vec![],
+            env: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            filesystem: Overlay::default(),
         }
     }
 }
@@ -48,6 +60,11 @@ struct AppState {
     buffer: TerminalBuffer,
     filesystem: Filesystem,
     history: History,
+    env: Environment,
+    aliases: Aliases,
+    status: i32,
+    /// The `su` waiting for its password
+    pending_su: Option<Su>,
     localstorage: LocalStorageSettings,
 }
 
@@ -78,6 +95,11 @@ impl AppState {
         &mut self.filesystem
     }
 
+    pub(crate) fn update_fs(&mut self) {
+        self.localstorage.filesystem = self.filesystem.overlay().clone();
+        let _ = gloo_storage::LocalStorage::set("settings", self.localstorage.clone());
+    }
+
     pub(crate) fn history(&self) -> &History {
         &self.history
     }
@@ -86,6 +108,49 @@ impl AppState {
         &mut self.history
     }
 
+    pub(crate) fn env(&self) -> &Environment {
+        &self.env
+    }
+
+    pub(crate) fn env_mut(&mut self) -> &mut Environment {
+        &mut self.env
+    }
+
+    pub(crate) fn update_env(&mut self) {
+        self.localstorage.env = self.env.exported();
+        let _ = gloo_storage::LocalStorage::set("settings", self.localstorage.clone());
+    }
+
+    pub(crate) fn aliases(&self) -> &Aliases {
+        &self.aliases
+    }
+
+    pub(crate) fn aliases_mut(&mut self) -> &mut Aliases {
+        &mut self.aliases
+    }
+
+    pub(crate) fn update_aliases(&mut self) {
+        self.localstorage.aliases = self.aliases.entries().clone();
+        let _ = gloo_storage::LocalStorage::set("settings", self.localstorage.clone());
+    }
+
+    /// The exit status of the last command, `$?`
+    pub(crate) fn status(&self) -> i32 {
+        self.status
+    }
+
+    pub(crate) fn set_status(&mut self, status: i32) {
+        self.status = status
+    }
+
+    pub(crate) fn pending_su(&self) -> Option<&Su> {
+        self.pending_su.as_ref()
+    }
+
+    pub(crate) fn set_pending_su(&mut self, su: Option<Su>) {
+        self.pending_su = su
+    }
+
     pub(crate) fn update_history(&mut self) {
         self.localstorage.history = self.history.entries().clone();
         let _ = gloo_storage::LocalStorage::set("settings", self.localstorage.clone());
@@ -95,6 +160,18 @@ impl AppState {
 #[component]
 fn App() -> Element {
     eval("document.body.onclick = () => {document.getElementById('input').focus()}");
+    // Clicking a link made by `command_link` puts its command on the prompt
+    eval(
+        r#"
+        document.addEventListener('click', (e) => {
+            const link = e.target.closest('[data-command]')
+            if (!link) return
+            const input = document.getElementById('input')
+            input.value = link.dataset.command
+            input.dispatchEvent(new Event('input', { bubbles: true }))
+        })
+        "#,
+    );
     eval(
         r#"
         document.body.onload = () => {
@@ -104,11 +181,7 @@ fn App() -> Element {
     );
 
     let settings =
-        if let Ok(settings) = gloo_storage::LocalStorage::get::<LocalStorageSettings>("settings") {
-            settings
-        } else {
-            LocalStorageSettings::default()
-        };
+        gloo_storage::LocalStorage::get::<LocalStorageSettings>("settings").unwrap_or_default();
 
     use_context_provider(|| {
         Signal::new(AppState {
@@ -117,8 +190,12 @@ fn App() -> Element {
                 _ => MOCHA,
             },
             buffer: TerminalBuffer::new(),
-            filesystem: Filesystem::new(),
+            filesystem: Filesystem::from(&settings),
             history: History::from(&settings),
+            env: Environment::from(&settings),
+            aliases: Aliases::from(&settings),
+            status: 0,
+            pending_su: None,
             localstorage: settings,
         })
     });
@@ -126,11 +203,10 @@ fn App() -> Element {
     let document = web_sys::window().unwrap().document().unwrap();
 
     if let Some(elem) = document.get_element_by_id("history") {
-        let mut observer_options = web_sys::MutationObserverInit::new();
-        observer_options
-            .attributes(true)
-            .child_list(true)
-            .character_data(true);
+        let observer_options = web_sys::MutationObserverInit::new();
+        observer_options.set_attributes(true);
+        observer_options.set_child_list(true);
+        observer_options.set_character_data(true);
 
         let observer = web_sys::MutationObserver::new(&Function::new_with_args(
             "mutations",
@@ -143,16 +219,17 @@ fn App() -> Element {
     }
 
     let state = consume_context::<Signal<AppState>>();
+    let state = state.read();
 
     rsx! {
         link { rel: "stylesheet", href: "main.css" }
         main {
             id: "main-container",
-            style: "background-color: {state().theme().crust.hex()}; color: {state().theme().text.hex()}",
-            if !state().buffer().is_empty() {
+            style: "background-color: {state.theme().crust.hex()}; color: {state.theme().text.hex()}",
+            if !state.buffer().is_empty() {
                 div { id: "history",
-                    for res in state().buffer().commands() {
-                        CmdOutput { cmd: &res.cmd, cmd_output: res.output.render(), failed: res.failed }
+                    for res in state.buffer().commands() {
+                        CmdOutput { cmd: &res.cmd, cmd_output: res.output.render(), status: res.status }
                     }
                 }
             }
@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+use super::lexer::{self, LexError, Token, WordPart};
+use crate::LocalStorageSettings;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Aliases {
+    aliases: BTreeMap<String, String>,
+}
+
+impl From<&LocalStorageSettings> for Aliases {
+    fn from(value: &LocalStorageSettings) -> Self {
+        Self {
+            aliases: value.aliases.clone(),
+        }
+    }
+}
+
+impl Aliases {
+    pub(crate) fn get(&self, name: &str) -> Option<&String> {
+        self.aliases.get(name)
+    }
+
+    pub(crate) fn set(&mut self, name: &str, value: &str) {
+        self.aliases.insert(name.to_string(), value.to_string());
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) -> Option<String> {
+        self.aliases.remove(name)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.aliases.clear()
+    }
+
+    pub(crate) fn entries(&self) -> &BTreeMap<String, String> {
+        &self.aliases
+    }
+
+    /// Replace every unquoted word in command position that names an alias with
+    /// the tokens of its value. An alias is not expanded again inside its own
+    /// expansion, so `alias ls='ls -l'` and aliases referring to each other terminate.
+    pub(crate) fn expand(&self, tokens: Vec<Token>) -> Result<Vec<Token>, LexError> {
+        self.expand_nested(tokens, &mut vec![])
+    }
+
+    fn expand_nested(
+        &self,
+        tokens: Vec<Token>,
+        active: &mut Vec<String>,
+    ) -> Result<Vec<Token>, LexError> {
+        let mut expanded = vec![];
+        let mut command_position = true;
+        let mut redirect_target = false;
+        for token in tokens {
+            match &token {
+                Token::Word(word) if command_position && !redirect_target => {
+                    command_position = false;
+                    if let [WordPart::Bare(name)] = word.parts() {
+                        if let Some(value) = self.get(name).filter(|_| !active.contains(name)) {
+                            active.push(name.clone());
+                            let tokens = lexer::tokenize(value)?;
+                            expanded.extend(self.expand_nested(tokens, active)?);
+                            active.pop();
+                            continue;
+                        }
+                    }
+                }
+                Token::Word(_) => redirect_target = false,
+                Token::Great | Token::DGreat | Token::Less => redirect_target = true,
+                Token::Pipe | Token::Semi | Token::AndIf | Token::OrIf | Token::Amp => {
+                    command_position = true
+                }
+            }
+            expanded.push(token);
+        }
+        Ok(expanded)
+    }
+}
+
+/// Whether `name` can be used as an alias
+pub(crate) fn is_alias_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name
+            .chars()
+            .any(|chr| chr.is_whitespace() || "'\"\\|&;<>()$`=/#".contains(chr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases(definitions: &[(&str, &str)]) -> Aliases {
+        let mut aliases = Aliases::from(&LocalStorageSettings::default());
+        for (name, value) in definitions {
+            aliases.set(name, value);
+        }
+        aliases
+    }
+
+    fn expand(aliases: &Aliases, input: &str) -> Vec<Token> {
+        aliases.expand(lexer::tokenize(input).unwrap()).unwrap()
+    }
+
+    fn tokens(input: &str) -> Vec<Token> {
+        lexer::tokenize(input).unwrap()
+    }
+
+    #[test]
+    fn only_words_in_command_position_expand() {
+        let aliases = aliases(&[("ll", "ls -l")]);
+        assert_eq!(expand(&aliases, "ll ll"), tokens("ls -l ll"));
+        assert_eq!(
+            expand(&aliases, "ll | ll; ll"),
+            tokens("ls -l | ls -l; ls -l")
+        );
+        assert_eq!(
+            expand(&aliases, "echo ll && ll"),
+            tokens("echo ll && ls -l")
+        );
+        assert_eq!(expand(&aliases, "> ll ll"), tokens("> ll ls -l"));
+    }
+
+    #[test]
+    fn quoted_words_do_not_expand() {
+        let aliases = aliases(&[("ll", "ls -l")]);
+        assert_eq!(expand(&aliases, "'ll'"), tokens("'ll'"));
+        assert_eq!(expand(&aliases, "\\ll"), tokens("\\ll"));
+    }
+
+    #[test]
+    fn an_alias_is_not_expanded_inside_itself() {
+        let recursive = aliases(&[("ls", "ls -a")]);
+        assert_eq!(expand(&recursive, "ls"), tokens("ls -a"));
+
+        let mutual = aliases(&[("a", "b 1"), ("b", "a 2")]);
+        assert_eq!(expand(&mutual, "a"), tokens("a 2 1"));
+        assert_eq!(expand(&mutual, "b"), tokens("b 1 2"));
+    }
+
+    #[test]
+    fn aliases_can_hold_command_lists() {
+        let aliases = aliases(&[("both", "pwd; ls"), ("ls", "ls -a")]);
+        assert_eq!(expand(&aliases, "both"), tokens("pwd; ls -a"));
+    }
+
+    #[test]
+    fn names_exclude_shell_syntax() {
+        assert!(is_alias_name("ll"));
+        assert!(is_alias_name("git-st"));
+        assert!(!is_alias_name(""));
+        assert!(!is_alias_name("a b"));
+        assert!(!is_alias_name("a=b"));
+        assert!(!is_alias_name("a/b"));
+        assert!(!is_alias_name("a|b"));
+    }
+}
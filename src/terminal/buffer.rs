@@ -1,4 +1,4 @@
-use super::commands::CommandResult;
+use super::commands::{escape_html, CommandResult};
 
 #[derive(Debug, Clone)]
 pub(crate) enum EntryContent {
@@ -19,33 +19,17 @@ impl EntryContent {
 pub(crate) struct CommandEntry {
     pub(crate) cmd: String,
     pub(crate) output: EntryContent,
-    pub(crate) failed: bool,
+    pub(crate) status: i32,
 }
 
 impl CommandEntry {
-    pub(crate) fn new(cmd: String, output: EntryContent, failed: bool) -> Self {
+    pub(crate) fn new(cmd: &str, output: EntryContent, status: i32) -> Self {
         Self {
-            cmd,
+            cmd: cmd.to_string(),
             output,
-            failed,
+            status,
         }
     }
-
-    pub(crate) fn ok(cmd: &str, output: EntryContent) -> Self {
-        Self::new(cmd.to_string(), output, false)
-    }
-
-    pub(crate) fn err(cmd: &str, output: EntryContent) -> Self {
-        Self::new(cmd.to_string(), output, true)
-    }
-
-    pub(crate) fn empty() -> Self {
-        Self::new("".to_string(), EntryContent::Empty, false)
-    }
-
-    pub(crate) fn no_output(cmd: &str) -> Self {
-        Self::new(cmd.to_string(), EntryContent::Empty, false)
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -62,36 +46,64 @@ impl TerminalBuffer {
         Self { commands: vec![] }
     }
 
-    pub(crate) fn process(&mut self, res: CommandResult) {
-        info!("Received command: {:?}", res);
-        match res {
-            CommandResult::None => self.commands.push(CommandEntry::empty()),
-            CommandResult::Help(help) => self
-                .commands
-                .push(CommandEntry::ok("help", EntryContent::Text(help))),
-            CommandResult::Clear => {
-                self.commands.push(CommandEntry::no_output("clear"));
-                self.clear();
+    /// Record a command line and the results of the pipelines it ran.
+    /// Only the last stage of each pipeline is rendered, the earlier stages
+    /// contribute their error messages.
+    pub(crate) fn process(&mut self, cmd: &str, pipelines: Vec<Vec<CommandResult>>, status: i32) {
+        info!("Received command: {:?}", pipelines);
+        let mut output = String::new();
+        // A trailing `clear` leaves an empty screen, without the command itself
+        let mut show_entry = true;
+        for results in pipelines {
+            let (last, earlier) = match results.split_last() {
+                Some(split) => split,
+                None => continue,
+            };
+            for res in earlier {
+                if let Some(err) = res.error() {
+                    output += &escape_html(&format!("{}\n", err.trim_end()));
+                }
+            }
+            match last {
+                CommandResult::Clear => {
+                    self.clear();
+                    output.clear();
+                    show_entry = false;
+                    continue;
+                }
+                CommandResult::Output(out) => {
+                    if !out.stderr.is_empty() {
+                        output += &escape_html(&format!("{}\n", out.stderr.trim_end()));
+                    }
+                    output += &out.render()
+                }
+                res => {
+                    let err = res.error().unwrap_or_default();
+                    output += &escape_html(&format!("{}\n", err.trim_end()))
+                }
             }
-            CommandResult::Pwd(pwd) => self
-                .commands
-                .push(CommandEntry::ok("pwd", EntryContent::Text(pwd.to_string()))),
-            CommandResult::Cd(cmd) => self.commands.push(CommandEntry::no_output(&cmd)),
-            CommandResult::Ls(ls) => self
-                .commands
-                .push(CommandEntry::ok("ls", EntryContent::Text(ls))),
-            CommandResult::Theme(cmd) => self.commands.push(CommandEntry::no_output(&cmd)),
-            CommandResult::History(cmd, history) => self
-                .commands
-                .push(CommandEntry::ok(&cmd, EntryContent::Text(history))),
-            CommandResult::Failed(cmd, output) => self
-                .commands
-                .push(CommandEntry::err(&cmd, EntryContent::Text(output))),
-            CommandResult::Unknown(cmd) => self.commands.push(CommandEntry::err(
-                &cmd,
-                EntryContent::Text(format!("command not found: {}", cmd)),
-            )),
+            show_entry = true;
+        }
+
+        if !show_entry {
+            return;
         }
+        let output = if output.is_empty() {
+            EntryContent::Empty
+        } else {
+            EntryContent::Text(output)
+        };
+        self.commands.push(CommandEntry::new(cmd, output, status));
+    }
+
+    /// Add `html` to the output of the last command and set its status, for a
+    /// command that finishes after more input like `su` once it has the password
+    pub(crate) fn finish_last(&mut self, html: &str, status: i32) {
+        let Some(last) = self.commands.last_mut() else {
+            return;
+        };
+        last.output = EntryContent::Text(last.output.render() + html);
+        last.status = status;
     }
 
     pub(crate) fn clear(&mut self) {
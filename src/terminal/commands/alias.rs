@@ -0,0 +1,53 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+use crate::terminal::alias::{is_alias_name, Aliases};
+
+/// Define or list aliases. They are kept for your next visit.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Alias {
+    /// NAME=VALUE to define an alias, NAME to print it
+    pub(crate) definitions: Vec<String>,
+}
+
+fn format_alias(name: &str, value: &str) -> String {
+    format!("alias {}='{}'\n", name, value.replace('\'', r"'\''"))
+}
+
+pub(crate) fn alias(args: &Alias, aliases: &mut Aliases) -> CommandResult {
+    if args.definitions.is_empty() {
+        return CommandResult::Output(Output::text(
+            aliases
+                .entries()
+                .iter()
+                .map(|(name, value)| format_alias(name, value))
+                .collect(),
+        ));
+    }
+
+    let mut stdout = String::new();
+    let mut errors = vec![];
+    for definition in &args.definitions {
+        match definition.split_once('=') {
+            Some((name, value)) if is_alias_name(name) => aliases.set(name, value),
+            Some((name, _)) => errors.push(format!("invalid alias name: {}", name)),
+            None => match aliases.get(definition) {
+                Some(value) => stdout += &format_alias(definition, value),
+                None => errors.push(format!("alias not found: {}", definition)),
+            },
+        }
+    }
+
+    CommandResult::from_errors(Output::text(stdout), errors)
+}
+
+impl Command for Alias {
+    const NAME: &'static str = "alias";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let res = alias(&self, ctx.state.aliases_mut());
+        ctx.state.update_aliases();
+        res
+    }
+}
@@ -0,0 +1,59 @@
+use clap::Parser;
+use markdown_to_html_parser::parse_markdown;
+
+use super::{escape_html, Command, CommandResult, Context, Output};
+
+/// Print the contents of files, rendering markdown
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Cat {
+    /// Print markdown files as they are instead of rendering them
+    #[arg(long)]
+    pub(crate) raw: bool,
+    /// The files to print, the input if none are given
+    pub(crate) paths: Vec<String>,
+}
+
+impl Command for Cat {
+    const NAME: &'static str = "cat";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        if self.paths.is_empty() {
+            return CommandResult::Output(Output::text(ctx.stdin.clone()));
+        }
+
+        let mut stdout = String::new();
+        let mut html = String::new();
+        let mut errors = vec![];
+        for path in &self.paths {
+            match ctx.state.fs().read_file(path) {
+                Ok(contents) => {
+                    stdout += &contents;
+                    html += &if path.ends_with(".md") && !self.raw {
+                        render_markdown(&contents)
+                    } else {
+                        escape_html(&contents)
+                    };
+                }
+                Err(err) => errors.push(err),
+            }
+        }
+
+        CommandResult::from_errors(Output::html(stdout, html), errors)
+    }
+}
+
+fn render_markdown(contents: &str) -> String {
+    // The parser passes text through as is, keep any markup in the file from reaching the page
+    let escaped = contents
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    // Headings are only recognized when followed by a newline
+    let escaped = if escaped.is_empty() || escaped.ends_with('\n') {
+        escaped
+    } else {
+        escaped + "\n"
+    };
+    parse_markdown(&escaped)
+}
@@ -1,9 +1,29 @@
-use clap::{command, Parser};
+use clap::Parser;
+
+use super::{Command, CommandResult, Context};
 
 /// Change directory
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub(crate) struct Cd {
-    /// The path to change directory to
-    pub(crate) path: String,
+    /// The path to change directory to, the home directory if not given
+    pub(crate) path: Option<String>,
+}
+
+impl Command for Cd {
+    const NAME: &'static str = "cd";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let Some(path) = self.path.or_else(|| ctx.state.env().get("HOME").cloned()) else {
+            return CommandResult::Failed("HOME not set".to_string());
+        };
+        match ctx.state.fs_mut().cd(&path) {
+            Ok(_) => {
+                let cwd = ctx.state.fs().cwd();
+                ctx.state.env_mut().change_dir(&cwd);
+                CommandResult::empty()
+            }
+            Err(err) => CommandResult::Failed(err),
+        }
+    }
 }
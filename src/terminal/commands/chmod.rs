@@ -0,0 +1,175 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+use crate::terminal::filesystem::{EXECUTE, READ, WRITE};
+
+/// Change the permissions of files
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Chmod {
+    /// Change the files and directories below the directories too
+    #[arg(short = 'R', long)]
+    pub(crate) recursive: bool,
+    /// The new mode, in octal like `755` or symbolic like `u+x,go-w`
+    #[arg(value_parser = parse_mode)]
+    pub(crate) mode: Mode,
+    /// The files to change
+    #[arg(required = true)]
+    pub(crate) paths: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Mode {
+    Octal(u32),
+    Symbolic(Vec<Clause>),
+}
+
+/// One comma separated part of a symbolic mode, like `go-w`
+#[derive(Debug, Clone)]
+pub(crate) struct Clause {
+    /// The bits of the classes of users it changes
+    who: u32,
+    /// The operator, `+`, `-` or `=`, with the permissions
+    actions: Vec<(char, Permissions)>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Permissions {
+    bits: u32,
+    /// `X`, execute only for directories and files that are executable for someone
+    search: bool,
+}
+
+fn parse_mode(mode: &str) -> Result<Mode, String> {
+    if mode.chars().all(|chr| chr.is_digit(8)) {
+        return match u32::from_str_radix(mode, 8) {
+            Ok(bits) if bits <= 0o7777 => Ok(Mode::Octal(bits & 0o777)),
+            _ => Err(format!("invalid mode: {}", mode)),
+        };
+    }
+    mode.split(',')
+        .map(|clause| parse_clause(clause).ok_or_else(|| format!("invalid mode: {}", mode)))
+        .collect::<Result<_, _>>()
+        .map(Mode::Symbolic)
+}
+
+fn parse_clause(clause: &str) -> Option<Clause> {
+    let mut chars = clause.chars().peekable();
+    let mut who = 0;
+    while let Some(class) = chars.next_if(|chr| "ugoa".contains(*chr)) {
+        who |= match class {
+            'u' => 0o700,
+            'g' => 0o070,
+            'o' => 0o007,
+            _ => 0o777,
+        };
+    }
+    if who == 0 {
+        who = 0o777;
+    }
+    let mut actions = vec![];
+    while let Some(op) = chars.next_if(|chr| "+-=".contains(*chr)) {
+        let mut permissions = Permissions::default();
+        while let Some(perm) = chars.next_if(|chr| "rwxX".contains(*chr)) {
+            match perm {
+                'r' => permissions.bits |= READ,
+                'w' => permissions.bits |= WRITE,
+                'x' => permissions.bits |= EXECUTE,
+                _ => permissions.search = true,
+            }
+        }
+        actions.push((op, permissions));
+    }
+    (!actions.is_empty() && chars.next().is_none()).then_some(Clause { who, actions })
+}
+
+impl Mode {
+    /// The new mode of a node with the mode `mode`
+    fn apply(&self, mut mode: u32, is_dir: bool) -> u32 {
+        let clauses = match self {
+            Mode::Octal(bits) => return *bits,
+            Mode::Symbolic(clauses) => clauses,
+        };
+        for clause in clauses {
+            for (op, permissions) in &clause.actions {
+                let mut bits = permissions.bits;
+                if permissions.search && (is_dir || mode & 0o111 != 0) {
+                    bits |= EXECUTE;
+                }
+                // The same bits for each class of users
+                let bits = (bits * 0o111) & clause.who;
+                mode = match op {
+                    '+' => mode | bits,
+                    '-' => mode & !bits,
+                    _ => mode & !clause.who | bits,
+                };
+            }
+        }
+        mode
+    }
+}
+
+impl Command for Chmod {
+    const NAME: &'static str = "chmod";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let errors = self
+            .paths
+            .iter()
+            .filter_map(|path| {
+                ctx.state
+                    .fs_mut()
+                    .chmod(path, self.recursive, |mode, is_dir| {
+                        self.mode.apply(mode, is_dir)
+                    })
+                    .err()
+            })
+            .collect::<Vec<_>>();
+        ctx.state.update_fs();
+
+        CommandResult::from_errors(Output::default(), errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(mode: &str, current: u32, is_dir: bool) -> u32 {
+        parse_mode(mode).unwrap().apply(current, is_dir)
+    }
+
+    #[test]
+    fn octal_modes_replace_the_mode() {
+        assert_eq!(apply("755", 0o644, false), 0o755);
+        assert_eq!(apply("0640", 0o777, true), 0o640);
+        assert_eq!(apply("0", 0o777, false), 0);
+        // The special bits are accepted but not kept
+        assert_eq!(apply("4755", 0o644, false), 0o755);
+    }
+
+    #[test]
+    fn symbolic_modes_change_the_mode() {
+        assert_eq!(apply("u+x", 0o644, false), 0o744);
+        assert_eq!(apply("+x", 0o644, false), 0o755);
+        assert_eq!(apply("go-r", 0o644, false), 0o600);
+        assert_eq!(apply("a=r", 0o755, false), 0o444);
+        assert_eq!(apply("o=", 0o777, false), 0o770);
+        assert_eq!(apply("u=rwx,g=rx,o=", 0o000, false), 0o750);
+        assert_eq!(apply("ug+w-x", 0o755, false), 0o665);
+    }
+
+    #[test]
+    fn capital_x_only_applies_to_directories_and_executables() {
+        assert_eq!(apply("a+X", 0o644, false), 0o644);
+        assert_eq!(apply("a+X", 0o644, true), 0o755);
+        assert_eq!(apply("a+X", 0o744, false), 0o755);
+    }
+
+    #[test]
+    fn invalid_modes_are_errors() {
+        for mode in ["8", "17777", "u", "u+z", "x+r", "u+r,", ""] {
+            assert!(parse_mode(mode).is_err(), "{} parsed", mode);
+        }
+    }
+}
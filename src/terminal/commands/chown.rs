@@ -0,0 +1,69 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+use crate::terminal::{filesystem::Filesystem, users};
+
+/// Change the owner and group of files
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Chown {
+    /// Change the files and directories below the directories too
+    #[arg(short = 'R', long)]
+    pub(crate) recursive: bool,
+    /// The new owner and group as `OWNER`, `OWNER:GROUP`, `:GROUP`, or
+    /// `OWNER:` for the group named like the owner
+    pub(crate) owner: String,
+    /// The files to change
+    #[arg(required = true)]
+    pub(crate) paths: Vec<String>,
+}
+
+/// The owner and the group to set, `None` for the ones that don't change. They
+/// must be in `/etc/passwd` and `/etc/group`.
+fn parse_owner<'a>(
+    spec: &'a str,
+    fs: &Filesystem,
+) -> Result<(Option<&'a str>, Option<&'a str>), String> {
+    let (owner, group) = match spec.split_once(':') {
+        Some((owner, "")) => (owner, owner),
+        Some((owner, group)) => (owner, group),
+        None => (spec, ""),
+    };
+    if owner.is_empty() && group.is_empty() {
+        return Err(format!("invalid owner: {}", spec));
+    }
+    if !owner.is_empty() && users::user(fs, owner).is_none() {
+        return Err(format!("invalid user: {}", owner));
+    }
+    if !group.is_empty() && !users::groups(fs).iter().any(|known| known.name == group) {
+        return Err(format!("invalid group: {}", group));
+    }
+    Ok((
+        (!owner.is_empty()).then_some(owner),
+        (!group.is_empty()).then_some(group),
+    ))
+}
+
+impl Command for Chown {
+    const NAME: &'static str = "chown";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let (owner, group) = match parse_owner(&self.owner, ctx.state.fs()) {
+            Ok(owner) => owner,
+            Err(err) => return CommandResult::Failed(err),
+        };
+        let errors = self
+            .paths
+            .iter()
+            .filter_map(|path| {
+                ctx.state
+                    .fs_mut()
+                    .chown(path, self.recursive, owner, group)
+                    .err()
+            })
+            .collect::<Vec<_>>();
+        ctx.state.update_fs();
+
+        CommandResult::from_errors(Output::default(), errors)
+    }
+}
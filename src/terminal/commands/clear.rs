@@ -1,6 +1,16 @@
-use clap::{command, Parser};
+use clap::Parser;
+
+use super::{Command, CommandResult, Context};
 
 /// Clear the console
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub(crate) struct Clear;
+
+impl Command for Clear {
+    const NAME: &'static str = "clear";
+
+    fn run(self, _ctx: &mut Context) -> CommandResult {
+        CommandResult::Clear
+    }
+}
@@ -0,0 +1,34 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+
+/// Copy files and directories
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Cp {
+    /// Copy directories and their contents
+    #[arg(short, long, visible_short_alias = 'R')]
+    pub(crate) recursive: bool,
+    /// The files to copy, followed by the destination
+    #[arg(required = true, num_args = 2..)]
+    pub(crate) paths: Vec<String>,
+}
+
+impl Command for Cp {
+    const NAME: &'static str = "cp";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let (dest, sources) = self.paths.split_last().expect("clap requires two paths");
+        if sources.len() > 1 && !ctx.state.fs().is_dir(dest) {
+            return CommandResult::Failed(format!("target is not a directory: {}", dest));
+        }
+
+        let errors = sources
+            .iter()
+            .filter_map(|src| ctx.state.fs_mut().copy(src, dest, self.recursive).err())
+            .collect::<Vec<_>>();
+        ctx.state.update_fs();
+
+        CommandResult::from_errors(Output::default(), errors)
+    }
+}
@@ -0,0 +1,32 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+
+/// Print the arguments
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Echo {
+    /// Do not print the trailing newline
+    #[arg(short = 'n')]
+    pub(crate) no_newline: bool,
+    /// The text to print
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub(crate) args: Vec<String>,
+}
+
+pub(crate) fn echo(args: &Echo) -> String {
+    let text = args.args.join(" ");
+    if args.no_newline {
+        text
+    } else {
+        text + "\n"
+    }
+}
+
+impl Command for Echo {
+    const NAME: &'static str = "echo";
+
+    fn run(self, _ctx: &mut Context) -> CommandResult {
+        CommandResult::Output(Output::text(echo(&self)))
+    }
+}
@@ -0,0 +1,24 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+use crate::terminal::environment::Environment;
+
+/// Print the environment variables
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Env;
+
+pub(crate) fn env(env: &Environment) -> String {
+    env.vars()
+        .iter()
+        .map(|(name, value)| format!("{}={}\n", name, value))
+        .collect()
+}
+
+impl Command for Env {
+    const NAME: &'static str = "env";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        CommandResult::Output(Output::text(env(ctx.state.env())))
+    }
+}
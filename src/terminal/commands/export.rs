@@ -0,0 +1,51 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+use crate::terminal::{environment::Environment, expand::is_name};
+
+/// Set environment variables. They are kept for your next visit.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Export {
+    /// NAME=VALUE to set a variable, NAME to keep its current value
+    pub(crate) assignments: Vec<String>,
+}
+
+pub(crate) fn export(args: &Export, env: &mut Environment) -> CommandResult {
+    if args.assignments.is_empty() {
+        return CommandResult::Output(Output::text(
+            env.exported()
+                .iter()
+                .map(|(name, value)| format!("declare -x {}=\"{}\"\n", name, value))
+                .collect(),
+        ));
+    }
+
+    let mut errors = vec![];
+    for assignment in &args.assignments {
+        let (name, value) = match assignment.split_once('=') {
+            Some((name, value)) => (name, value.to_string()),
+            None => (
+                assignment.as_str(),
+                env.get(assignment).cloned().unwrap_or_default(),
+            ),
+        };
+        if is_name(name) {
+            env.export(name, &value);
+        } else {
+            errors.push(format!("not a valid identifier: {}", assignment));
+        }
+    }
+
+    CommandResult::from_errors(Output::default(), errors)
+}
+
+impl Command for Export {
+    const NAME: &'static str = "export";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let res = export(&self, ctx.state.env_mut());
+        ctx.state.update_env();
+        res
+    }
+}
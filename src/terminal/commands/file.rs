@@ -0,0 +1,119 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+use crate::terminal::filesystem::{Filesystem, LsResultType};
+
+/// Guess what kind of content files hold
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct File {
+    /// The files to describe
+    #[arg(required = true)]
+    pub(crate) paths: Vec<String>,
+}
+
+/// What a MIME type is called, `None` for plain text
+fn kind(mime: &str) -> Option<&'static str> {
+    Some(match mime {
+        "text/markdown" => "Markdown document",
+        "text/x-rust" => "Rust source",
+        "application/toml" => "TOML document",
+        "application/json" => "JSON data",
+        "application/yaml" => "YAML document",
+        "text/html" => "HTML document",
+        "text/css" => "CSS stylesheet",
+        "text/javascript" => "JavaScript source",
+        "text/x-shellscript" => "shell script",
+        "text/x-python" => "Python script",
+        "text/csv" => "CSV text",
+        "application/xml" => "XML document",
+        "image/svg+xml" => "SVG image",
+        _ => return None,
+    })
+}
+
+/// The description of the file at `path`
+fn describe(fs: &Filesystem, path: &str) -> Result<String, String> {
+    let entry = fs.entry(path)?;
+    match entry.ty() {
+        LsResultType::Directory => return Ok("directory".to_string()),
+        LsResultType::Link => {
+            let target = entry.target().unwrap_or_default();
+            // The target only has to exist, it may well be unreadable
+            return Ok(if fs.canonicalize(path).is_ok() {
+                format!("symbolic link to {}", target)
+            } else {
+                format!("broken symbolic link to {}", target)
+            });
+        }
+        LsResultType::File if entry.mime() == "inode/chardevice" => {
+            return Ok("character special".to_string())
+        }
+        LsResultType::File => {}
+    }
+    let contents = fs
+        .read_file(path)
+        .map_err(|_| format!("cannot open: {}", path))?;
+    if contents.is_empty() {
+        return Ok("empty".to_string());
+    }
+    let encoding = if contents.is_ascii() {
+        "ASCII text"
+    } else {
+        "Unicode text, UTF-8 text"
+    };
+    Ok(match kind(entry.mime()) {
+        Some(kind) => format!("{}, {}", kind, encoding),
+        None => encoding.to_string(),
+    })
+}
+
+impl Command for File {
+    const NAME: &'static str = "file";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let fs = ctx.state.fs();
+        let mut stdout = String::new();
+        let mut errors = vec![];
+        for path in &self.paths {
+            match describe(fs, path) {
+                Ok(description) => stdout += &format!("{}: {}\n", path, description),
+                Err(err) => errors.push(err),
+            }
+        }
+
+        CommandResult::from_errors(Output::text(stdout), errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LocalStorageSettings;
+
+    #[test]
+    fn links_are_broken_only_when_the_target_is_missing() {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.mkdir("/tmp/file", true).unwrap();
+        fs.write_file("/tmp/file/secret", "", false).unwrap();
+        fs.chmod("/tmp/file/secret", false, |_, _| 0o200).unwrap();
+        fs.symlink("secret", "/tmp/file/to-secret", false).unwrap();
+        fs.symlink("/tmp/file", "/tmp/file/to-dir", false).unwrap();
+        fs.symlink("missing", "/tmp/file/to-missing", false)
+            .unwrap();
+
+        assert_eq!(
+            describe(&fs, "/tmp/file/to-secret").unwrap(),
+            "symbolic link to secret"
+        );
+        assert_eq!(
+            describe(&fs, "/tmp/file/to-dir").unwrap(),
+            "symbolic link to /tmp/file"
+        );
+        assert_eq!(
+            describe(&fs, "/tmp/file/to-missing").unwrap(),
+            "broken symbolic link to missing"
+        );
+        assert!(describe(&fs, "/tmp/file/secret").is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use clap::Parser;
+
+use super::{command_link, escape_html, Command, CommandResult, Context, Output};
+use crate::terminal::{
+    completion::escape,
+    filesystem::{Filesystem, LsResultType},
+    glob,
+};
+
+/// Search for files in a directory hierarchy
+///
+/// The expression is made of `-name GLOB`, `-type f|d|l`, `-maxdepth N` and `-mindepth N`.
+/// The results are full paths; clicking one puts `cd` or `cat` for it on the prompt.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about)]
+pub(crate) struct Find {
+    /// The directories to search, the current directory if none are given, followed by the expression
+    #[arg(allow_hyphen_values = true, value_name = "PATH... EXPRESSION")]
+    pub(crate) args: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct Expression {
+    name: Option<String>,
+    ty: Option<char>,
+    min_depth: usize,
+    max_depth: Option<usize>,
+}
+
+impl Expression {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut expression = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing argument: {}", arg))
+            };
+            let depth = |value: &String| {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid argument to {}: {}", arg, value))
+            };
+            match arg.as_str() {
+                "-name" => expression.name = Some(value()?.clone()),
+                "-type" => match value()?.as_str() {
+                    ty @ ("f" | "d" | "l") => expression.ty = ty.chars().next(),
+                    ty => return Err(format!("unknown argument to -type: {}", ty)),
+                },
+                "-mindepth" => expression.min_depth = depth(value()?)?,
+                "-maxdepth" => expression.max_depth = Some(depth(value()?)?),
+                arg => return Err(format!("unknown predicate: {}", arg)),
+            }
+        }
+        Ok(expression)
+    }
+
+    fn matches(&self, name: &str, ty: &LsResultType, depth: usize) -> bool {
+        let ty_matches = match self.ty {
+            Some('f') => matches!(ty, LsResultType::File),
+            Some('d') => matches!(ty, LsResultType::Directory),
+            Some('l') => matches!(ty, LsResultType::Link),
+            _ => true,
+        };
+        depth >= self.min_depth
+            && ty_matches
+            && self
+                .name
+                .as_ref()
+                .is_none_or(|pattern| glob::matches(pattern, name))
+    }
+}
+
+/// The matching paths, as text and as links to `cd` into or `cat` them
+#[derive(Default)]
+struct Results {
+    stdout: String,
+    html: String,
+}
+
+impl Results {
+    fn push(&mut self, fs: &Filesystem, path: &str) {
+        let command = if fs.is_dir(path) { "cd" } else { "cat" };
+        self.stdout += &format!("{}\n", path);
+        self.html += &command_link(&escape_html(path), &format!("{} {}", command, escape(path)));
+        self.html += "<br />";
+    }
+}
+
+/// Walk the tree below `path`, which is at `depth`, printing the matching entries
+fn walk(
+    fs: &Filesystem,
+    expression: &Expression,
+    path: &str,
+    depth: usize,
+    results: &mut Results,
+    errors: &mut Vec<String>,
+) {
+    if expression.max_depth.is_some_and(|max| depth >= max) {
+        return;
+    }
+    let entries = match fs.list(path) {
+        Ok(entries) => entries,
+        // Like GNU find, go on with the rest of the tree
+        Err(_) => return errors.push(format!("'{}': Permission denied", path)),
+    };
+    for entry in entries {
+        let entry_path = format!("{}/{}", path.trim_end_matches('/'), entry.name());
+        if expression.matches(entry.name(), entry.ty(), depth + 1) {
+            results.push(fs, &entry_path);
+        }
+        if let LsResultType::Directory = entry.ty() {
+            walk(fs, expression, &entry_path, depth + 1, results, errors);
+        }
+    }
+}
+
+pub(crate) fn find(args: &Find, fs: &Filesystem) -> CommandResult {
+    let split = args
+        .args
+        .iter()
+        .position(|arg| arg.starts_with('-'))
+        .unwrap_or(args.args.len());
+    let (paths, expression) = args.args.split_at(split);
+    let expression = match Expression::parse(expression) {
+        Ok(expression) => expression,
+        Err(err) => return CommandResult::Usage(err),
+    };
+    let paths = if paths.is_empty() {
+        vec![".".to_string()]
+    } else {
+        paths.to_vec()
+    };
+
+    let mut results = Results::default();
+    let mut errors = vec![];
+    for path in &paths {
+        let entry = match fs.entry(path) {
+            Ok(entry) => entry,
+            Err(err) => {
+                errors.push(err);
+                continue;
+            }
+        };
+        let name = match path.trim_end_matches('/') {
+            "" => "/",
+            trimmed => trimmed.rsplit('/').next().unwrap_or(trimmed),
+        };
+        // The results are full paths, whatever the start was given as
+        let path = fs.path(path).to_string();
+        if expression.matches(name, entry.ty(), 0) {
+            results.push(fs, &path);
+        }
+        if let LsResultType::Directory = entry.ty() {
+            walk(fs, &expression, &path, 0, &mut results, &mut errors);
+        }
+    }
+
+    CommandResult::from_errors(Output::html(results.stdout, results.html), errors)
+}
+
+impl Command for Find {
+    const NAME: &'static str = "find";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        find(&self, ctx.state.fs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LocalStorageSettings;
+
+    /// A filesystem with `a.md`, `sub/b.md`, `sub/deep/c.txt`, a link `l` to
+    /// `sub` and an unreadable `locked/` in `/tmp/f`, which is the working directory
+    fn filesystem() -> Filesystem {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.mkdir("/tmp/f/sub/deep", true).unwrap();
+        fs.mkdir("/tmp/f/locked", true).unwrap();
+        for file in ["a.md", "sub/b.md", "sub/deep/c.txt", "locked/d.md"] {
+            fs.write_file(&format!("/tmp/f/{}", file), "", false)
+                .unwrap();
+        }
+        fs.symlink("sub", "/tmp/f/l", false).unwrap();
+        fs.chmod("/tmp/f/locked", false, |_, _| 0o311).unwrap();
+        fs.cd("/tmp/f").unwrap();
+        fs
+    }
+
+    fn find(args: &str) -> (Vec<String>, String) {
+        let args = Find {
+            args: args.split_whitespace().map(str::to_string).collect(),
+        };
+        match super::find(&args, &filesystem()) {
+            CommandResult::Output(output) => {
+                let mut paths = output
+                    .stdout
+                    .lines()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>();
+                paths.sort();
+                (paths, output.stderr)
+            }
+            res => (vec![], res.error().unwrap_or_default()),
+        }
+    }
+
+    #[test]
+    fn names_match_globs() {
+        assert_eq!(find(". -name *.md").0, ["/tmp/f/a.md", "/tmp/f/sub/b.md"]);
+        assert_eq!(find("sub -name c.*").0, ["/tmp/f/sub/deep/c.txt"]);
+        assert_eq!(find("/tmp/f -name f").0, ["/tmp/f"]);
+    }
+
+    #[test]
+    fn types_select_files_directories_or_links() {
+        assert_eq!(
+            find(". -type d").0,
+            ["/tmp/f", "/tmp/f/locked", "/tmp/f/sub", "/tmp/f/sub/deep"]
+        );
+        assert_eq!(find(". -type l").0, ["/tmp/f/l"]);
+        assert_eq!(
+            find(". -type f").0,
+            ["/tmp/f/a.md", "/tmp/f/sub/b.md", "/tmp/f/sub/deep/c.txt"]
+        );
+    }
+
+    #[test]
+    fn depths_bound_the_walk() {
+        assert_eq!(find(". -maxdepth 0").0, ["/tmp/f"]);
+        assert_eq!(
+            find(". -mindepth 2 -type f").0,
+            ["/tmp/f/sub/b.md", "/tmp/f/sub/deep/c.txt"]
+        );
+        assert_eq!(
+            find(". -mindepth 1 -maxdepth 1 -type d").0,
+            ["/tmp/f/locked", "/tmp/f/sub"]
+        );
+    }
+
+    #[test]
+    fn the_root_is_named_slash() {
+        assert_eq!(find("/ -maxdepth 0 -name /").0, ["/"]);
+        assert_eq!(find("// -maxdepth 0 -name /").0, ["/"]);
+        assert!(find("/ -maxdepth 0 -name tmp").0.is_empty());
+    }
+
+    #[test]
+    fn unreadable_directories_are_reported() {
+        let (paths, errors) = find("locked");
+        assert_eq!(paths, ["/tmp/f/locked"]);
+        assert_eq!(errors, "'/tmp/f/locked': Permission denied");
+    }
+
+    #[test]
+    fn invalid_expressions_are_usage_errors() {
+        let parse = |args: &[&str]| Find {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        };
+        let fs = filesystem();
+        for args in [
+            vec!["-name"],
+            vec!["-type", "x"],
+            vec!["-maxdepth", "a"],
+            vec!["-x"],
+        ] {
+            assert_eq!(super::find(&parse(&args), &fs).status(), 2);
+        }
+    }
+}
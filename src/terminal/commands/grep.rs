@@ -0,0 +1,326 @@
+use clap::Parser;
+use regex::{Regex, RegexBuilder};
+
+use super::{escape_html, Command, CommandResult, Context, Output};
+use crate::{
+    terminal::filesystem::{Filesystem, LsResultType},
+    ui::themes::TerminalTheme,
+};
+
+/// Print the lines that match a regular expression
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Grep {
+    /// Search the files in directories, recursively
+    #[arg(short, long)]
+    pub(crate) recursive: bool,
+    /// Ignore case distinctions
+    #[arg(short, long)]
+    pub(crate) ignore_case: bool,
+    /// Print the line number before each line
+    #[arg(short = 'n', long)]
+    pub(crate) line_number: bool,
+    /// Print only the names of the files with matches
+    #[arg(short = 'l', long)]
+    pub(crate) files_with_matches: bool,
+    /// Print the lines that do not match instead
+    #[arg(short = 'v', long)]
+    pub(crate) invert_match: bool,
+    /// Print only the number of matching lines
+    #[arg(short, long)]
+    pub(crate) count: bool,
+    /// Use extended regular expressions, where `+ ? | ( ) { }` are special without a backslash
+    #[arg(short = 'E', long)]
+    pub(crate) extended_regexp: bool,
+    /// The regular expression to search for
+    pub(crate) pattern: String,
+    /// The files to search, the input if none are given
+    pub(crate) files: Vec<String>,
+}
+
+/// Translate a basic regular expression, where `+ ? | ( ) { }` are only
+/// special after a backslash, to the extended syntax
+fn basic_to_extended(pattern: &str) -> String {
+    let mut extended = String::new();
+    let mut chars = pattern.chars();
+    while let Some(chr) = chars.next() {
+        match chr {
+            '\\' => match chars.next() {
+                Some(next) if "+?|(){}".contains(next) => extended.push(next),
+                Some(next) => {
+                    extended.push('\\');
+                    extended.push(next);
+                }
+                None => extended.push_str("\\\\"),
+            },
+            chr if "+?|(){}".contains(chr) => {
+                extended.push('\\');
+                extended.push(chr);
+            }
+            chr => extended.push(chr),
+        }
+    }
+    extended
+}
+
+struct Search<'a> {
+    args: &'a Grep,
+    regex: Regex,
+    /// Whether lines are prefixed with the name of their file
+    prefix: bool,
+    theme: &'a TerminalTheme,
+    stdout: String,
+    html: String,
+    matched: bool,
+}
+
+impl Search<'_> {
+    fn prefix(&self, name: &str, sep: char) -> (String, String) {
+        (
+            format!("{}{}", name, sep),
+            format!(
+                "<span style='{}'>{}</span>{}",
+                self.theme.sapphire.style_text(),
+                escape_html(name),
+                sep
+            ),
+        )
+    }
+
+    fn push(&mut self, (text, html): (String, String)) {
+        self.stdout += &text;
+        self.html += &html;
+    }
+
+    /// The line with its matches highlighted
+    fn highlight(&self, line: &str) -> String {
+        let mut html = String::new();
+        let mut last = 0;
+        for found in self.regex.find_iter(line).filter(|found| !found.is_empty()) {
+            html += &escape_html(&line[last..found.start()]);
+            html += &format!(
+                "<b style='{}'>{}</b>",
+                self.theme.red.style_text(),
+                escape_html(found.as_str())
+            );
+            last = found.end();
+        }
+        html + &escape_html(&line[last..])
+    }
+
+    /// Search `contents`, printing its lines or count as `name`
+    fn search(&mut self, name: &str, contents: &str) {
+        let selected = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| self.regex.is_match(line) != self.args.invert_match)
+            .collect::<Vec<_>>();
+        self.matched |= !selected.is_empty();
+
+        if self.args.files_with_matches {
+            if !selected.is_empty() {
+                let (text, html) = self.prefix(name, '\n');
+                self.push((text, html.replace('\n', "<br />")));
+            }
+            return;
+        }
+        if self.args.count {
+            if self.prefix {
+                self.push(self.prefix(name, ':'));
+            }
+            self.push((
+                format!("{}\n", selected.len()),
+                format!("{}<br />", selected.len()),
+            ));
+            return;
+        }
+        for (idx, line) in selected {
+            if self.prefix {
+                self.push(self.prefix(name, ':'));
+            }
+            if self.args.line_number {
+                self.push((
+                    format!("{}:", idx + 1),
+                    format!(
+                        "<span style='{}'>{}</span>:",
+                        self.theme.green.style_text(),
+                        idx + 1
+                    ),
+                ));
+            }
+            let html = if self.args.invert_match {
+                escape_html(line)
+            } else {
+                self.highlight(line)
+            };
+            self.push((format!("{}\n", line), html + "<br />"));
+        }
+    }
+
+    /// Search the file at `path`, and everything below it if it is a directory and
+    /// the search is recursive. `top` is whether the path was given on the command line.
+    fn search_path(&mut self, fs: &Filesystem, path: &str, top: bool, errors: &mut Vec<String>) {
+        let entry = match fs.entry(path) {
+            Ok(entry) => entry,
+            Err(err) => return errors.push(err),
+        };
+        let is_dir = match entry.ty() {
+            LsResultType::Directory => true,
+            // Like GNU grep, links are only followed when given on the command line
+            LsResultType::Link if !top => return,
+            LsResultType::Link => fs.is_dir(path),
+            LsResultType::File => false,
+        };
+        if !is_dir {
+            match fs.read_file(path) {
+                Ok(contents) => self.search(path, &contents),
+                Err(err) => errors.push(err),
+            }
+            return;
+        }
+        if !self.args.recursive {
+            return errors.push(format!("is a directory: {}", path));
+        }
+        match fs.list(path) {
+            Ok(entries) => {
+                for entry in entries {
+                    let entry_path = format!("{}/{}", path.trim_end_matches('/'), entry.name());
+                    self.search_path(fs, &entry_path, false, errors);
+                }
+            }
+            Err(err) => errors.push(err),
+        }
+    }
+}
+
+pub(crate) fn grep(
+    args: &Grep,
+    fs: &Filesystem,
+    input: &str,
+    theme: &TerminalTheme,
+) -> CommandResult {
+    let pattern = if args.extended_regexp {
+        args.pattern.clone()
+    } else {
+        basic_to_extended(&args.pattern)
+    };
+    let regex = match RegexBuilder::new(&pattern)
+        .case_insensitive(args.ignore_case)
+        .build()
+    {
+        Ok(regex) => regex,
+        Err(err) => return CommandResult::Usage(err.to_string()),
+    };
+
+    let mut files = args.files.clone();
+    if files.is_empty() && args.recursive {
+        files.push(".".to_string());
+    }
+    let mut search = Search {
+        args,
+        regex,
+        prefix: args.recursive || files.len() > 1,
+        theme,
+        stdout: String::new(),
+        html: String::new(),
+        matched: false,
+    };
+    let mut errors = vec![];
+    if files.is_empty() {
+        search.search("(standard input)", input);
+    }
+    for file in &files {
+        search.search_path(fs, file, true, &mut errors);
+    }
+
+    let status = match (errors.is_empty(), search.matched) {
+        (false, _) => 2,
+        (true, true) => 0,
+        (true, false) => 1,
+    };
+    CommandResult::Output(
+        Output::html(search.stdout, search.html)
+            .with_errors(errors)
+            .with_status(status),
+    )
+}
+
+impl Command for Grep {
+    const NAME: &'static str = "grep";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        grep(&self, ctx.state.fs(), &ctx.stdin, ctx.state.theme())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ui::themes::MOCHA, LocalStorageSettings};
+
+    /// A filesystem with `a.md` and `sub/b.md` in `/tmp/grep`, which is the working directory
+    fn filesystem() -> Filesystem {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.mkdir("/tmp/grep/sub", true).unwrap();
+        fs.write_file("/tmp/grep/a.md", "one\ntwo\nthree\n", false)
+            .unwrap();
+        fs.write_file("/tmp/grep/sub/b.md", "four\nfive\n", false)
+            .unwrap();
+        fs.cd("/tmp/grep").unwrap();
+        fs
+    }
+
+    /// The output and status of `grep args` with `input` as stdin
+    fn grep_input(args: &[&str], input: &str) -> (String, i32) {
+        let args = Grep::try_parse_from(std::iter::once(&"grep").chain(args)).unwrap();
+        let res = super::grep(&args, &filesystem(), input, &MOCHA);
+        match &res {
+            CommandResult::Output(output) => (output.stdout.clone(), res.status()),
+            res => panic!("grep failed: {:?}", res),
+        }
+    }
+
+    fn grep(args: &[&str]) -> (String, i32) {
+        grep_input(args, "")
+    }
+
+    #[test]
+    fn matching_lines_are_printed() {
+        assert_eq!(grep(&["t", "a.md"]), ("two\nthree\n".to_string(), 0));
+        assert_eq!(grep(&["^T", "-i", "a.md"]).0, "two\nthree\n");
+        assert_eq!(grep(&["-n", "e$", "a.md"]).0, "1:one\n3:three\n");
+        assert_eq!(grep(&["x", "a.md"]), ("".to_string(), 1));
+        assert_eq!(grep_input(&["b"], "abc\nxyz").0, "abc\n");
+    }
+
+    #[test]
+    fn inverted_and_counted_matches() {
+        assert_eq!(grep(&["-v", "t", "a.md"]).0, "one\n");
+        assert_eq!(grep(&["-c", "t", "a.md"]).0, "2\n");
+        assert_eq!(grep(&["-cv", "t", "a.md"]).0, "1\n");
+        assert_eq!(grep(&["-c", "x", "a.md"]), ("0\n".to_string(), 1));
+    }
+
+    #[test]
+    fn recursive_searches_prefix_the_file_names() {
+        assert_eq!(grep(&["-r", "f"]).0, "./sub/b.md:four\n./sub/b.md:five\n");
+        assert_eq!(grep(&["-rl", "o"]).0, "./a.md\n./sub/b.md\n");
+        assert_eq!(grep(&["-rc", "o", "sub"]).0, "sub/b.md:1\n");
+        assert_eq!(grep(&["-l", "o", "a.md", "sub/b.md"]).0, "a.md\nsub/b.md\n");
+    }
+
+    #[test]
+    fn errors_exit_with_status_two() {
+        let (stdout, status) = grep(&["o", "a.md", "missing", "sub"]);
+        assert_eq!(stdout, "a.md:one\na.md:two\n");
+        assert_eq!(status, 2);
+    }
+
+    #[test]
+    fn basic_expressions_escape_extended_operators() {
+        assert_eq!(basic_to_extended("a+b"), "a\\+b");
+        assert_eq!(basic_to_extended("a\\+b"), "a+b");
+        assert_eq!(basic_to_extended("\\(a\\|b\\)"), "(a|b)");
+        assert_eq!(basic_to_extended("a\\.b"), "a\\.b");
+    }
+}
@@ -0,0 +1,27 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+
+/// Print the first lines of the input
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Head {
+    /// The number of lines to print
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub(crate) lines: usize,
+}
+
+pub(crate) fn head(args: &Head, input: &str) -> String {
+    input
+        .lines()
+        .take(args.lines)
+        .fold("".to_string(), |acc, line| acc + line + "\n")
+}
+
+impl Command for Head {
+    const NAME: &'static str = "head";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        CommandResult::Output(Output::text(head(&self, &ctx.stdin)))
+    }
+}
@@ -1,5 +1,6 @@
-use clap::{command, Parser};
+use clap::Parser;
 
+use super::{registry::registry, Command, CommandResult, Context, Output};
 use crate::ui::themes::TerminalTheme;
 
 /// List available commands
@@ -7,19 +8,44 @@ use crate::ui::themes::TerminalTheme;
 #[command(version, about, long_about = None)]
 pub(crate) struct Help;
 
-pub(crate) fn help(theme: &TerminalTheme) -> String {
-    format!(
+pub(crate) fn help(theme: &TerminalTheme) -> Output {
+    let width = registry()
+        .commands()
+        .map(|command| command.name().len())
+        .max()
+        .unwrap_or(0);
+    let list = registry()
+        .commands()
+        .map(|command| {
+            format!(
+                "- {:width$}  {}\n",
+                command.name(),
+                command.description(),
+                width = width
+            )
+        })
+        .collect::<String>();
+    let stdout = format!(
+        "Available commands:\n{}\nRun [command] --help to get help for a specific command\n",
+        list
+    );
+    let html = format!(
         r#"
         <b style='{}'>Available commands:</b>
         <br />
-        - help <br />
-        - pwd <br />
-        - cd <br />
-        - ls <br />
-        - theme <br />
-        - history <br />
+        <span style='white-space: pre'>{}</span>
         <br />
         Run [command] --help to get help for a specific command"#,
-        theme.peach.style_text()
-    )
+        theme.peach.style_text(),
+        list.replace('\n', "<br />")
+    );
+    Output::html(stdout, html)
+}
+
+impl Command for Help {
+    const NAME: &'static str = "help";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        CommandResult::Output(help(ctx.state.theme()))
+    }
 }
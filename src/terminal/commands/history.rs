@@ -1,4 +1,6 @@
-use clap::{command, Parser};
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
 
 /// View and clear the command history
 #[derive(Parser, Debug)]
@@ -7,3 +9,22 @@ pub(crate) struct History {
     #[arg(long)]
     pub(crate) clear: bool,
 }
+
+impl Command for History {
+    const NAME: &'static str = "history";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        if self.clear {
+            ctx.state.history_mut().clear();
+            ctx.state.update_history();
+        }
+
+        CommandResult::Output(Output::text(
+            ctx.state
+                .history()
+                .entries()
+                .iter()
+                .fold("".to_string(), |acc, e| acc + e + "\n"),
+        ))
+    }
+}
@@ -0,0 +1,44 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+use crate::terminal::{filesystem::Filesystem, users};
+
+/// Print the user and group ids of a user
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Id {
+    /// The user to describe, the current user if not given
+    pub(crate) user: Option<String>,
+}
+
+pub(crate) fn id(args: &Id, fs: &Filesystem) -> Result<String, String> {
+    let name = args.user.as_deref().unwrap_or(fs.user());
+    let user = users::user(fs, name).ok_or_else(|| format!("no such user: {}", name))?;
+    let groups = users::groups_of(fs, &user);
+    let primary = groups
+        .iter()
+        .find(|group| group.gid == user.gid)
+        .map_or(user.gid.to_string(), |group| {
+            format!("{}({})", group.gid, group.name)
+        });
+    let groups = groups
+        .iter()
+        .map(|group| format!("{}({})", group.gid, group.name))
+        .collect::<Vec<_>>()
+        .join(",");
+    Ok(format!(
+        "uid={}({}) gid={} groups={}\n",
+        user.uid, user.name, primary, groups
+    ))
+}
+
+impl Command for Id {
+    const NAME: &'static str = "id";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        match id(&self, ctx.state.fs()) {
+            Ok(output) => CommandResult::Output(Output::text(output)),
+            Err(err) => CommandResult::Failed(err),
+        }
+    }
+}
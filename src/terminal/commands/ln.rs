@@ -0,0 +1,40 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+
+/// Make links between files
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Ln {
+    /// Make a symbolic link. Hard links are not supported.
+    #[arg(short, long)]
+    pub(crate) symbolic: bool,
+    /// Replace an existing file at the link name
+    #[arg(short, long)]
+    pub(crate) force: bool,
+    /// What the link points to. A relative target is relative to the directory of the link.
+    pub(crate) target: String,
+    /// Where to create the link, a file named like the target in the current directory if not given
+    pub(crate) link_name: Option<String>,
+}
+
+impl Command for Ln {
+    const NAME: &'static str = "ln";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        if !self.symbolic {
+            return CommandResult::Failed("hard links are not supported, use -s".to_string());
+        }
+        let link_name = self.link_name.as_deref().unwrap_or(".");
+        let res = ctx
+            .state
+            .fs_mut()
+            .symlink(&self.target, link_name, self.force);
+        ctx.state.update_fs();
+
+        match res {
+            Ok(_) => CommandResult::Output(Output::default()),
+            Err(err) => CommandResult::Failed(err),
+        }
+    }
+}
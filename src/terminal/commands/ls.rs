@@ -1,41 +1,109 @@
-use clap::{command, Parser};
+use clap::{ArgAction, Parser};
 
+use super::{escape_html, Command, CommandResult, Context, Output};
 use crate::{
-    terminal::filesystem::{Filesystem, LsResult, LsResultType},
+    terminal::{
+        filesystem::{now, Filesystem, LsResult, LsResultType},
+        time::format_short,
+    },
     ui::themes::TerminalTheme,
 };
 
-/// List files in the current directory
+/// The width the grid of names is fitted to
+const GRID_WIDTH: usize = 80;
+
+/// List directory contents
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
-pub(crate) struct Ls;
+#[command(version, about, long_about = None, disable_help_flag = true)]
+pub(crate) struct Ls {
+    /// Show entries starting with `.`
+    #[arg(short, long)]
+    pub(crate) all: bool,
+    /// Use the long listing format
+    #[arg(short)]
+    pub(crate) long: bool,
+    /// List subdirectories recursively
+    #[arg(short = 'R', long)]
+    pub(crate) recursive: bool,
+    /// Print sizes like 1.5K
+    #[arg(short, long)]
+    pub(crate) human_readable: bool,
+    /// Sort by size, largest first
+    #[arg(short = 'S')]
+    pub(crate) sort_size: bool,
+    /// Sort by modification time, newest first
+    #[arg(short = 't')]
+    pub(crate) sort_time: bool,
+    /// Reverse the order
+    #[arg(short, long)]
+    pub(crate) reverse: bool,
+    /// List one entry per line
+    #[arg(short = '1')]
+    pub(crate) one_per_line: bool,
+    /// Print help
+    #[arg(long, action = ArgAction::Help)]
+    pub(crate) help: Option<bool>,
+    /// The files and directories to list, the current directory if none are given
+    pub(crate) paths: Vec<String>,
+}
+
+fn size(entry: &LsResult, human: bool) -> String {
+    match entry.size() {
+        Some(size) if human => human_size(size),
+        Some(size) => size.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+fn human_size(size: u64) -> String {
+    if size < 1024 {
+        return size.to_string();
+    }
+    let mut value = size as f64;
+    for unit in ["K", "M", "G", "T"] {
+        value /= 1024.0;
+        if value < 1024.0 || unit == "T" {
+            return if value < 10.0 {
+                format!("{:.1}{}", value, unit)
+            } else {
+                format!("{:.0}{}", value, unit)
+            };
+        }
+    }
+    unreachable!()
+}
 
-fn longest_size(entries: &Vec<LsResult>) -> usize {
+fn longest_size(entries: &[LsResult], human: bool) -> usize {
     entries
         .iter()
-        .map(|lsr| lsr.size().map_or("-".to_string(), |x| x.to_string()).len())
+        .map(|lsr| size(lsr, human).len())
         .max()
         .unwrap_or(1)
 }
 
-fn pad(num: Option<u64>, max: usize) -> String {
-    let str = num.map_or("-".to_string(), |x| x.to_string());
-
-    if str.len() == max {
+fn pad(str: String, max: usize) -> String {
+    if str.len() >= max {
         return str;
     }
 
     let spaces = " "
         .chars()
-        .into_iter()
         .cycle()
-        .take((max - str.len()) as usize)
+        .take(max - str.len())
         .collect::<String>();
 
     spaces + &str
 }
 
-fn format_name(name: &str, ty: &LsResultType, theme: &TerminalTheme) -> String {
+pub(crate) fn plain_name(name: &str, ty: &LsResultType) -> String {
+    match ty {
+        LsResultType::Directory => format!("{}/", name),
+        LsResultType::File | LsResultType::Link => name.to_string(),
+    }
+}
+
+pub(crate) fn format_name(name: &str, ty: &LsResultType, theme: &TerminalTheme) -> String {
+    let name = escape_html(name);
     match ty {
         LsResultType::Directory => {
             format!(
@@ -57,23 +125,328 @@ fn format_name(name: &str, ty: &LsResultType, theme: &TerminalTheme) -> String {
     }
 }
 
-pub(crate) fn ls(filesystem: &Filesystem, theme: &TerminalTheme) -> String {
-    let res = match filesystem.ls() {
-        Ok(entries) => {
-            let max_n = longest_size(&entries);
-            entries
+/// A group of entries printed together, the contents of a directory or the files given as arguments
+struct Section {
+    header: Option<String>,
+    entries: Vec<LsResult>,
+}
+
+impl Ls {
+    fn sort(&self, entries: &mut [LsResult]) {
+        entries.sort_by(|a, b| a.name().cmp(b.name()));
+        if self.sort_size {
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.size().unwrap_or(0)));
+        } else if self.sort_time {
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.modified()));
+        }
+        if self.reverse {
+            entries.reverse();
+        }
+    }
+
+    /// List the directory `path`, and its subdirectories with `-R`
+    fn list_dir(
+        &self,
+        fs: &Filesystem,
+        path: &str,
+        sections: &mut Vec<Section>,
+        errors: &mut Vec<String>,
+    ) {
+        let mut entries = match fs.list(path) {
+            Ok(entries) => entries,
+            Err(err) => return errors.push(err),
+        };
+        entries.retain(|entry| self.all || !entry.name().starts_with('.'));
+        if self.all {
+            for special in ["..", "."] {
+                if let Ok(entry) = fs.entry(&format!("{}/{}", path, special)) {
+                    entries.insert(0, entry.renamed(special));
+                }
+            }
+        }
+        self.sort(&mut entries);
+
+        let subdirs = entries
+            .iter()
+            .filter(|entry| matches!(entry.ty(), LsResultType::Directory))
+            .filter(|entry| entry.name() != "." && entry.name() != "..")
+            .map(|entry| format!("{}/{}", path.trim_end_matches('/'), entry.name()))
+            .collect::<Vec<_>>();
+        sections.push(Section {
+            header: Some(path.to_string()),
+            entries,
+        });
+        if self.recursive {
+            for subdir in subdirs {
+                self.list_dir(fs, &subdir, sections, errors);
+            }
+        }
+    }
+
+    /// The plain text and html of the entries of a section. The text is for the
+    /// next command of a pipeline, so it has one undecorated name per line;
+    /// the grid and the `/` after directories are only in the html.
+    fn render(&self, entries: &[LsResult], theme: &TerminalTheme) -> (String, String) {
+        if self.long {
+            let max_n = longest_size(entries, self.human_readable);
+            let longest = |field: fn(&LsResult) -> &str| {
+                entries
+                    .iter()
+                    .map(|entry| field(entry).chars().count())
+                    .max()
+                    .unwrap_or(0)
+            };
+            let max_owner = longest(LsResult::owner);
+            let max_group = longest(LsResult::group);
+            let now = now();
+            // `text` makes the owner and the group fit for the output, escaping them in the html
+            let line = |entry: &LsResult, name: String, text: fn(&str) -> String| {
+                format!(
+                    "{} {} {} {} {} {}",
+                    entry.permissions(),
+                    text(&format!("{:<max_owner$}", entry.owner())),
+                    text(&format!("{:<max_group$}", entry.group())),
+                    pad(size(entry, self.human_readable), max_n),
+                    format_short(entry.modified(), now),
+                    name
+                )
+            };
+            let stdout = entries
+                .iter()
+                .map(|entry| {
+                    let name = entry.name().to_string();
+                    match entry.target() {
+                        Some(target) => {
+                            line(entry, format!("{} -> {}", name, target), str::to_string)
+                        }
+                        None => line(entry, name, str::to_string),
+                    }
+                })
+                .fold("".to_string(), |acc, e| acc + &e + "\n");
+            let html = entries
                 .iter()
                 .map(|entry| {
-                    format!(
-                        "{} {} {}",
-                        entry.permissions(),
-                        pad(entry.size(), max_n),
-                        format_name(entry.name(), &entry.ty(), &theme)
-                    )
+                    let name = format_name(entry.name(), entry.ty(), theme);
+                    match entry.target() {
+                        Some(target) => line(
+                            entry,
+                            format!("{} -> {}", name, escape_html(target)),
+                            escape_html,
+                        ),
+                        None => line(entry, name, escape_html),
+                    }
                 })
-                .fold("".to_string(), |acc, e| acc + &e + "<br />")
+                .fold("".to_string(), |acc, e| acc + &e + "<br />");
+            return (stdout, html);
         }
-        Err(err) => err,
+
+        let stdout = entries
+            .iter()
+            .map(|entry| format!("{}\n", entry.name()))
+            .collect();
+        let names = entries
+            .iter()
+            .map(|entry| plain_name(entry.name(), entry.ty()))
+            .collect::<Vec<_>>();
+        let (rows, widths) = if self.one_per_line {
+            (entries.len(), vec![0])
+        } else {
+            grid(&names)
+        };
+        let mut html = String::new();
+        for row in 0..rows {
+            for (col, width) in widths.iter().enumerate() {
+                let Some(entry) = entries.get(col * rows + row) else {
+                    continue;
+                };
+                let name = &names[col * rows + row];
+                // No padding after the last name of a line
+                let last = col + 1 == widths.len() || col * rows + row + rows >= entries.len();
+                let padding = if last {
+                    "".to_string()
+                } else {
+                    " ".repeat(width - name.chars().count())
+                };
+                html += &format!(
+                    "{}{}",
+                    format_name(entry.name(), entry.ty(), theme),
+                    padding
+                );
+            }
+            html += "<br />";
+        }
+        (stdout, html)
+    }
+}
+
+/// The fewest rows the names fit in when laid out in columns, with the width of each column
+fn grid(names: &[String]) -> (usize, Vec<usize>) {
+    for rows in 1..names.len() {
+        let widths = names
+            .chunks(rows)
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|name| name.chars().count())
+                    .max()
+                    .unwrap_or(0)
+                    + 2
+            })
+            .collect::<Vec<_>>();
+        if widths.iter().sum::<usize>() - 2 <= GRID_WIDTH {
+            return (rows, widths);
+        }
+    }
+    (names.len(), vec![0])
+}
+
+pub(crate) fn ls(args: &Ls, filesystem: &Filesystem, theme: &TerminalTheme) -> CommandResult {
+    let paths = if args.paths.is_empty() {
+        vec![".".to_string()]
+    } else {
+        args.paths.clone()
     };
-    res
+
+    let mut errors = vec![];
+    let mut files = vec![];
+    let mut dirs = vec![];
+    for path in &paths {
+        match filesystem.entry(path) {
+            Ok(entry)
+                if matches!(entry.ty(), LsResultType::Directory) || filesystem.is_dir(path) =>
+            {
+                dirs.push(path)
+            }
+            Ok(entry) => files.push(entry.renamed(path)),
+            Err(err) => errors.push(err),
+        }
+    }
+    args.sort(&mut files);
+
+    let mut sections = vec![];
+    if !files.is_empty() {
+        sections.push(Section {
+            header: None,
+            entries: files,
+        });
+    }
+    for dir in dirs {
+        args.list_dir(filesystem, dir, &mut sections, &mut errors);
+    }
+    // Directories are only named when there is more than one thing listed
+    if sections.len() == 1 && !args.recursive {
+        sections[0].header = None;
+    }
+
+    let mut stdout = String::new();
+    let mut html = String::new();
+    for (idx, section) in sections.iter().enumerate() {
+        if idx > 0 {
+            stdout += "\n";
+            html += "<br />";
+        }
+        if let Some(header) = &section.header {
+            stdout += &format!("{}:\n", header);
+            html += &format!("{}:<br />", escape_html(header));
+        }
+        let (text, markup) = args.render(&section.entries, theme);
+        stdout += &text;
+        html += &markup;
+    }
+    CommandResult::from_errors(
+        Output::html(
+            stdout,
+            format!("<span style='white-space: pre'>{}</span>", html),
+        ),
+        errors,
+    )
+}
+
+impl Command for Ls {
+    const NAME: &'static str = "ls";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        ls(&self, ctx.state.fs(), ctx.state.theme())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{terminal::filesystem::ROOT, ui::themes::MOCHA, LocalStorageSettings};
+
+    /// A filesystem with `b.md` (3 bytes), `a.md` (1 byte), `c/` and `.hidden`
+    /// in `/tmp/l`, which is the working directory
+    fn filesystem() -> Filesystem {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.mkdir("/tmp/l/c", true).unwrap();
+        fs.write_file("/tmp/l/b.md", "bbb", false).unwrap();
+        fs.write_file("/tmp/l/a.md", "a", false).unwrap();
+        fs.write_file("/tmp/l/.hidden", "", false).unwrap();
+        fs.cd("/tmp/l").unwrap();
+        fs
+    }
+
+    fn ls(fs: &Filesystem, args: &[&str]) -> Output {
+        let args = Ls::try_parse_from(std::iter::once(&"ls").chain(args)).unwrap();
+        match super::ls(&args, fs, &MOCHA) {
+            CommandResult::Output(output) => output,
+            res => panic!("ls failed: {:?}", res),
+        }
+    }
+
+    fn names(fs: &Filesystem, args: &[&str]) -> Vec<String> {
+        ls(fs, args).stdout.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn entries_are_sorted_by_name_size_or_reversed() {
+        let fs = filesystem();
+        assert_eq!(names(&fs, &[]), ["a.md", "b.md", "c"]);
+        assert_eq!(
+            names(&fs, &["-a"]),
+            [".", "..", ".hidden", "a.md", "b.md", "c"]
+        );
+        assert_eq!(names(&fs, &["-r"]), ["c", "b.md", "a.md"]);
+        assert_eq!(names(&fs, &["-S"]), ["b.md", "a.md", "c"]);
+        assert_eq!(names(&fs, &["-Sr"]), ["c", "a.md", "b.md"]);
+    }
+
+    #[test]
+    fn the_pipeline_gets_one_undecorated_name_per_line() {
+        let fs = filesystem();
+        let output = ls(&fs, &[]);
+        assert_eq!(output.stdout, "a.md\nb.md\nc\n");
+        assert!(output.html.unwrap().contains("c/"));
+        assert_eq!(names(&fs, &["/tmp/l/a.md", "c"]), ["/tmp/l/a.md", "", "c:"]);
+    }
+
+    #[test]
+    fn names_fill_the_fewest_rows_that_fit() {
+        let short = ["a", "bb", "ccc"].map(str::to_string);
+        assert_eq!(grid(&short), (1, vec![3, 4, 5]));
+
+        let long = (0..6)
+            .map(|idx| format!("{}{}", idx, "x".repeat(29)))
+            .collect::<Vec<_>>();
+        let (rows, widths) = grid(&long);
+        assert_eq!(rows, 3);
+        assert_eq!(widths, [32, 32]);
+
+        let wide = ["x".repeat(100), "y".to_string()];
+        assert_eq!(grid(&wide), (2, vec![0]));
+    }
+
+    #[test]
+    fn long_listings_escape_owners_and_groups() {
+        let mut fs = filesystem();
+        fs.login(ROOT, vec![]);
+        fs.chown("/tmp/l/a.md", false, Some("<b>"), Some("&"))
+            .unwrap();
+        let output = ls(&fs, &["-l", "a.md"]);
+        assert!(output.stdout.starts_with("-rw-r--r-- <b> &"));
+        let html = output.html.unwrap();
+        assert!(html.contains("&lt;b&gt; &amp;"));
+        assert!(!html.contains("<b>"));
+    }
 }
@@ -0,0 +1,30 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+
+/// Create directories
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Mkdir {
+    /// Create the missing parent directories too, and accept existing ones
+    #[arg(short, long)]
+    pub(crate) parents: bool,
+    /// The directories to create
+    #[arg(required = true)]
+    pub(crate) paths: Vec<String>,
+}
+
+impl Command for Mkdir {
+    const NAME: &'static str = "mkdir";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let errors = self
+            .paths
+            .iter()
+            .filter_map(|path| ctx.state.fs_mut().mkdir(path, self.parents).err())
+            .collect::<Vec<_>>();
+        ctx.state.update_fs();
+
+        CommandResult::from_errors(Output::default(), errors)
+    }
+}
@@ -3,117 +3,360 @@ use std::error::Error;
 use clap::Parser;
 use dioxus::{
     prelude::consume_context,
-    signals::{Signal, Writable},
+    signals::{Readable, Signal, Writable},
 };
+use registry::registry;
 
 use crate::{
-    ui::themes::{LATTE, MOCHA},
+    terminal::{
+        expand::{expand_fields, expand_word},
+        glob,
+        parser::{self, Connector, RedirectKind, SimpleCommand},
+    },
     AppState,
 };
 
+mod alias;
+mod cat;
 mod cd;
+mod chmod;
+mod chown;
 mod clear;
+mod cp;
+mod echo;
+mod env;
+mod export;
+mod file;
+mod find;
+mod grep;
+mod head;
 mod help;
 mod history;
+mod id;
+mod ln;
 mod ls;
+mod mkdir;
+mod mv;
 mod pwd;
+mod readlink;
+pub(crate) mod registry;
+mod reset_fs;
+mod rm;
+mod shopt;
+mod stat;
+pub(crate) mod su;
 mod theme;
+mod touch;
+mod tree;
+mod unalias;
+mod unset;
+mod wc;
+mod whoami;
+
+/// What a command writes to stdout. `stdout` is plain text and is what the next
+/// command of a pipeline reads, `html` is an optional richer rendering for the terminal.
+/// `stderr` holds errors of a command that still produced output, like `cat` with a missing file.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Output {
+    pub(crate) stdout: String,
+    pub(crate) html: Option<String>,
+    pub(crate) stderr: String,
+    pub(crate) status: i32,
+}
+
+impl Output {
+    pub(crate) fn text(stdout: String) -> Self {
+        Self {
+            stdout,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn html(stdout: String, html: String) -> Self {
+        Self {
+            stdout,
+            html: Some(html),
+            ..Default::default()
+        }
+    }
+
+    /// Exit with a non-zero status without printing an error, like `grep` without matches
+    pub(crate) fn with_status(mut self, status: i32) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub(crate) fn with_stderr(mut self, stderr: String) -> Self {
+        self.stderr = stderr;
+        self
+    }
+
+    /// Print `errors` to stderr and exit with status 1 if there are any
+    pub(crate) fn with_errors(self, errors: Vec<String>) -> Self {
+        if errors.is_empty() {
+            return self;
+        }
+        self.with_stderr(errors.join("\n")).with_status(1)
+    }
+
+    pub(crate) fn render(&self) -> String {
+        match &self.html {
+            Some(html) => html.clone(),
+            None => escape_html(&self.stdout),
+        }
+    }
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br />")
+}
+
+/// `html` as a link that puts `command` on the prompt when it is clicked, see `App`
+pub(crate) fn command_link(html: &str, command: &str) -> String {
+    format!(
+        "<span data-command=\"{}\" style='cursor: pointer; text-decoration: underline'>{}</span>",
+        escape_html(command).replace('"', "&quot;"),
+        html
+    )
+}
+
+/// What a command gets to work with
+pub(crate) struct Context<'a> {
+    pub(crate) state: &'a mut AppState,
+    pub(crate) stdin: String,
+    /// Whether nothing runs after the command on the line, so that it may
+    /// leave the prompt waiting for input, like `su` for a password
+    pub(crate) last: bool,
+}
+
+/// A shell command. clap parses the arguments into `Self`, which then runs.
+/// To make a command available, add it to the [`registry`].
+pub(crate) trait Command: Parser {
+    /// The name the command is invoked with
+    const NAME: &'static str;
+
+    fn run(self, ctx: &mut Context) -> CommandResult;
+}
 
 #[derive(Debug, Clone)]
 pub(crate) enum CommandResult {
+    Output(Output),
+    /// A runtime error, exit status 1
+    Failed(String),
+    /// Invalid syntax or arguments, exit status 2
+    Usage(String),
+    /// Exit status 127
     Unknown(String),
-    Failed(String, String),
-    Help(String),
-    Pwd(&'static str),
-    Cd(String),
-    Ls(String),
-    Theme(String),
-    History(String, String),
     Clear,
-    None,
 }
 
 impl CommandResult {
-    fn from_err<T>(value: T, cmd: &str) -> Self
+    fn from_err<T>(value: T) -> Self
     where
         T: Error,
     {
-        CommandResult::Failed(
-            cmd.to_string(),
-            value
-                .to_string()
-                .replace("<", "&lt;")
-                .replace(">", "&gt;")
-                .replace("\n", "<br />"),
-        )
+        CommandResult::Usage(value.to_string())
+    }
+
+    /// `--help` and `--version` are reported by clap as errors, but they are regular output
+    fn from_clap(err: clap::Error) -> Self {
+        if err.exit_code() == 0 {
+            CommandResult::Output(Output::text(err.to_string()))
+        } else {
+            CommandResult::from_err(err)
+        }
+    }
+
+    pub(crate) fn status(&self) -> i32 {
+        match self {
+            CommandResult::Output(output) => output.status,
+            CommandResult::Failed(_) => 1,
+            CommandResult::Usage(_) => 2,
+            CommandResult::Unknown(_) => 127,
+            CommandResult::Clear => 0,
+        }
+    }
+
+    fn empty() -> Self {
+        CommandResult::Output(Output::default())
+    }
+
+    /// The result of a command that went on after `errors`, like `cat` with a
+    /// missing file. If it printed nothing else, the command failed.
+    pub(crate) fn from_errors(output: Output, errors: Vec<String>) -> Self {
+        if !errors.is_empty() && output.stdout.is_empty() {
+            CommandResult::Failed(errors.join("\n"))
+        } else {
+            CommandResult::Output(output.with_errors(errors))
+        }
+    }
+
+    pub(crate) fn error(&self) -> Option<String> {
+        match self {
+            CommandResult::Failed(err) | CommandResult::Usage(err) => Some(err.clone()),
+            CommandResult::Unknown(cmd) => Some(format!("command not found: {}", cmd)),
+            CommandResult::Output(output) if !output.stderr.is_empty() => {
+                Some(output.stderr.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn stdout(&self) -> String {
+        match self {
+            CommandResult::Output(output) => output.stdout.clone(),
+            _ => "".to_string(),
+        }
     }
 }
 
-pub(crate) fn exec(mut cmd: String) {
+pub(crate) fn exec(cmd: String) {
     let mut state = consume_context::<Signal<AppState>>();
 
-    if cmd.is_empty() {
-        state.write().buffer_mut().process(CommandResult::None);
-        return;
-    }
-    cmd = cmd.trim().to_string();
-    let cmd_parts = cmd.split(" ").collect::<Vec<_>>();
-    let res = match *cmd_parts.get(0).unwrap() {
-        "help" => match help::Help::try_parse_from(cmd_parts) {
-            Ok(_) => CommandResult::Help(help::help(state().theme())),
-            Err(err) => CommandResult::from_err(err, "help"),
-        },
-        "clear" => match clear::Clear::try_parse_from(cmd_parts) {
-            Ok(_) => CommandResult::Clear,
-            Err(err) => CommandResult::from_err(err, "clear"),
-        },
-        "pwd" => match pwd::Pwd::try_parse_from(cmd_parts) {
-            Ok(_) => CommandResult::Pwd(pwd::pwd(state().fs())),
-            Err(err) => CommandResult::from_err(err, "pwd"),
-        },
-        "cd" => match cd::Cd::try_parse_from(cmd_parts) {
-            Ok(cd) => match state.write().fs_mut().cd(cd.path) {
-                Ok(_) => CommandResult::Cd(cmd),
-                Err(err) => CommandResult::Failed("cd".to_string(), err),
-            },
-            Err(err) => CommandResult::from_err(err, "cd"),
-        },
-        "theme" => match theme::Theme::try_parse_from(cmd_parts) {
-            Ok(theme) => {
-                let theme = match theme.theme {
-                    theme::ThemeName::Latte => LATTE,
-                    theme::ThemeName::Frappe => todo!(),
-                    theme::ThemeName::Macchiato => todo!(),
-                    theme::ThemeName::Mocha => MOCHA,
-                };
-                state.write().change_theme(theme);
-                CommandResult::Theme(cmd)
+    let cmd = cmd.trim().to_string();
+    let parsed = parser::parse(&cmd, state.read().aliases());
+    let list = match parsed {
+        Ok(list) => list,
+        Err(err) => {
+            let res = CommandResult::from_err(err);
+            let status = res.status();
+            state.write().set_status(status);
+            state
+                .write()
+                .buffer_mut()
+                .process(&cmd, vec![vec![res]], status);
+            return;
+        }
+    };
+
+    let mut pipelines = vec![];
+    let items = list.items.len();
+    for (idx, (connector, pipeline)) in list.items.into_iter().enumerate() {
+        let status = state.read().status();
+        let skip = match connector {
+            Connector::Always => false,
+            Connector::And => status != 0,
+            Connector::Or => status == 0,
+        };
+        if skip {
+            continue;
+        }
+
+        let mut results = Vec::<CommandResult>::new();
+        let stages = pipeline.stages.len();
+        for (stage_idx, stage) in pipeline.stages.into_iter().enumerate() {
+            let stdin = results.last().map(CommandResult::stdout);
+            let last = stage_idx + 1 == stages && idx + 1 == items;
+            results.push(run_stage(&stage, stdin, last));
+        }
+        let status = results.last().map_or(0, CommandResult::status);
+        state.write().set_status(status);
+        pipelines.push(results);
+    }
+    let status = state.read().status();
+    state.write().buffer_mut().process(&cmd, pipelines, status);
+}
+
+/// Run a single pipeline stage, applying its redirections around the command.
+/// `last` is whether it is the last command of the line.
+fn run_stage(stage: &SimpleCommand, mut stdin: Option<String>, last: bool) -> CommandResult {
+    let mut state = consume_context::<Signal<AppState>>();
+    let (status, env) = {
+        let state = state.read();
+        (state.status(), state.env().clone())
+    };
+    let lookup = |name: &str| match name {
+        "?" => Some(status.to_string()),
+        name => env.get(name).cloned(),
+    };
+
+    // Like a shell, open (and truncate) every target before running the command
+    let mut stdout_target = None;
+    for redirect in &stage.redirects {
+        let path = match expand_word(&redirect.target, &lookup).as_deref() {
+            Ok([path]) => path.to_string(),
+            Ok(_) => return CommandResult::Failed("ambiguous redirect".to_string()),
+            Err(err) => return CommandResult::Failed(err.clone()),
+        };
+        let res = match redirect.kind {
+            RedirectKind::Input => state
+                .read()
+                .fs()
+                .read_file(&path)
+                .map(|contents| stdin = Some(contents.to_string())),
+            RedirectKind::Output | RedirectKind::Append => {
+                let append = redirect.kind == RedirectKind::Append;
+                let res = state.write().fs_mut().write_file(&path, "", append);
+                state.write().update_fs();
+                stdout_target = Some(path);
+                res
             }
-            Err(err) => CommandResult::from_err(err, "cd"),
-        },
-        "ls" => match ls::Ls::try_parse_from(cmd_parts) {
-            Ok(_) => CommandResult::Ls(ls::ls(state().fs(), state().theme())),
-            Err(err) => CommandResult::from_err(err, "ls"),
-        },
-        "history" => match history::History::try_parse_from(cmd_parts) {
-            Ok(history) => {
-                if history.clear {
-                    state.write().history_mut().clear();
-                    state.write().update_history();
-                }
+        };
+        if let Err(err) = res {
+            return CommandResult::Failed(err);
+        }
+    }
 
-                CommandResult::History(
-                    cmd,
-                    state()
-                        .history()
-                        .entries()
-                        .iter()
-                        .fold("".to_string(), |acc, e| acc + e + "<br />"),
-                )
+    let mut cmd_parts = vec![];
+    // The filesystem is borrowed once for every pattern of the command
+    let fs_state = state.read();
+    let fs = fs_state.fs();
+    for word in &stage.words {
+        let fields = match expand_fields(word, &lookup) {
+            Ok(fields) => fields,
+            Err(err) => return CommandResult::Failed(err),
+        };
+        for field in fields {
+            match glob::expand_field(field, fs, env.option("failglob")) {
+                Ok(args) => cmd_parts.extend(args),
+                Err(err) => return CommandResult::Failed(err),
             }
-            Err(err) => CommandResult::from_err(err, "clear-history"),
-        },
-        other => CommandResult::Unknown(other.to_string()),
+        }
+    }
+    drop(fs_state);
+    let res = if cmd_parts.is_empty() {
+        CommandResult::empty()
+    } else {
+        run(cmd_parts, stdin, last)
     };
-    state.write().buffer_mut().process(res);
+
+    match (stdout_target, res) {
+        (Some(path), CommandResult::Output(output)) => {
+            let res = state
+                .write()
+                .fs_mut()
+                .write_file(&path, &output.stdout, true);
+            match res {
+                Ok(_) => {
+                    state.write().update_fs();
+                    CommandResult::Output(
+                        Output::default()
+                            .with_stderr(output.stderr)
+                            .with_status(output.status),
+                    )
+                }
+                Err(err) => CommandResult::Failed(err),
+            }
+        }
+        (_, res) => res,
+    }
+}
+
+fn run(cmd_parts: Vec<String>, stdin: Option<String>, last: bool) -> CommandResult {
+    let mut state = consume_context::<Signal<AppState>>();
+
+    match registry().get(&cmd_parts[0]) {
+        Some(command) => command.run(
+            cmd_parts,
+            &mut Context {
+                state: &mut state.write(),
+                stdin: stdin.unwrap_or_default(),
+                last,
+            },
+        ),
+        None => CommandResult::Unknown(cmd_parts[0].clone()),
+    }
 }
@@ -0,0 +1,31 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+
+/// Move or rename files and directories
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Mv {
+    /// The files to move, followed by the destination
+    #[arg(required = true, num_args = 2..)]
+    pub(crate) paths: Vec<String>,
+}
+
+impl Command for Mv {
+    const NAME: &'static str = "mv";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let (dest, sources) = self.paths.split_last().expect("clap requires two paths");
+        if sources.len() > 1 && !ctx.state.fs().is_dir(dest) {
+            return CommandResult::Failed(format!("target is not a directory: {}", dest));
+        }
+
+        let errors = sources
+            .iter()
+            .filter_map(|src| ctx.state.fs_mut().rename(src, dest).err())
+            .collect::<Vec<_>>();
+        ctx.state.update_fs();
+
+        CommandResult::from_errors(Output::default(), errors)
+    }
+}
@@ -1,12 +1,32 @@
-use clap::{command, Parser};
+use clap::Parser;
 
+use super::{Command, CommandResult, Context, Output};
 use crate::terminal::filesystem::Filesystem;
 
 /// Print the working directory
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
-pub(crate) struct Pwd;
+pub(crate) struct Pwd {
+    /// Print the directory as it was reached, through any links (the default)
+    #[arg(short = 'L', long, overrides_with = "physical")]
+    pub(crate) logical: bool,
+    /// Print the directory with every link resolved
+    #[arg(short = 'P', long, overrides_with = "logical")]
+    pub(crate) physical: bool,
+}
+
+pub(crate) fn pwd(args: &Pwd, filesystem: &Filesystem) -> String {
+    if args.physical {
+        filesystem.physical_cwd()
+    } else {
+        filesystem.cwd()
+    }
+}
+
+impl Command for Pwd {
+    const NAME: &'static str = "pwd";
 
-pub(crate) fn pwd(filesystem: &Filesystem) -> &'static str {
-    filesystem.cwd()
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        CommandResult::Output(Output::text(format!("{}\n", pwd(&self, ctx.state.fs()))))
+    }
 }
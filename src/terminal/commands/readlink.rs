@@ -0,0 +1,38 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+
+/// Print what a link points to
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Readlink {
+    /// Print the absolute path with every link resolved, the last component doesn't have to exist
+    #[arg(short = 'f', long)]
+    pub(crate) canonicalize: bool,
+    /// The links to read
+    #[arg(required = true)]
+    pub(crate) paths: Vec<String>,
+}
+
+impl Command for Readlink {
+    const NAME: &'static str = "readlink";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let fs = ctx.state.fs();
+        let mut stdout = String::new();
+        let mut errors = vec![];
+        for path in &self.paths {
+            let res = if self.canonicalize {
+                fs.canonicalize(path)
+            } else {
+                fs.read_link(path).map(str::to_string)
+            };
+            match res {
+                Ok(target) => stdout += &format!("{}\n", target),
+                Err(err) => errors.push(err),
+            }
+        }
+
+        CommandResult::from_errors(Output::text(stdout), errors)
+    }
+}
@@ -0,0 +1,112 @@
+use std::marker::PhantomData;
+
+use lazy_static::lazy_static;
+
+use super::{
+    alias, cat, cd, chmod, chown, clear, cp, echo, env, export, file, find, grep, head, help,
+    history, id, ln, ls, mkdir, mv, pwd, readlink, reset_fs, rm, shopt, stat, su, theme, touch,
+    tree, unalias, unset, wc, whoami, Command, CommandResult, Context,
+};
+
+lazy_static! {
+    static ref REGISTRY: Registry = Registry::default()
+        .register::<help::Help>()
+        .register::<clear::Clear>()
+        .register::<pwd::Pwd>()
+        .register::<cd::Cd>()
+        .register::<ls::Ls>()
+        .register::<tree::Tree>()
+        .register::<find::Find>()
+        .register::<cat::Cat>()
+        .register::<stat::Stat>()
+        .register::<file::File>()
+        .register::<mkdir::Mkdir>()
+        .register::<touch::Touch>()
+        .register::<rm::Rm>()
+        .register::<mv::Mv>()
+        .register::<cp::Cp>()
+        .register::<chmod::Chmod>()
+        .register::<chown::Chown>()
+        .register::<ln::Ln>()
+        .register::<readlink::Readlink>()
+        .register::<reset_fs::ResetFs>()
+        .register::<whoami::Whoami>()
+        .register::<id::Id>()
+        .register::<su::Su>()
+        .register::<theme::Theme>()
+        .register::<history::History>()
+        .register::<echo::Echo>()
+        .register::<export::Export>()
+        .register::<unset::Unset>()
+        .register::<shopt::Shopt>()
+        .register::<env::Env>()
+        .register::<alias::Alias>()
+        .register::<unalias::Unalias>()
+        .register::<grep::Grep>()
+        .register::<head::Head>()
+        .register::<wc::Wc>();
+}
+
+pub(crate) fn registry() -> &'static Registry {
+    &REGISTRY
+}
+
+/// The object safe view of a [`Command`] that the registry stores
+pub(crate) trait RegisteredCommand: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn parser(&self) -> clap::Command;
+    fn description(&self) -> String;
+    fn run(&self, argv: Vec<String>, ctx: &mut Context) -> CommandResult;
+}
+
+struct Registration<T>(PhantomData<fn() -> T>);
+
+impl<T: Command> RegisteredCommand for Registration<T> {
+    fn name(&self) -> &'static str {
+        T::NAME
+    }
+
+    fn parser(&self) -> clap::Command {
+        T::command().name(T::NAME)
+    }
+
+    fn description(&self) -> String {
+        self.parser()
+            .get_about()
+            .map_or("".to_string(), |about| about.to_string())
+    }
+
+    fn run(&self, argv: Vec<String>, ctx: &mut Context) -> CommandResult {
+        match self
+            .parser()
+            .try_get_matches_from(argv)
+            .and_then(|matches| T::from_arg_matches(&matches))
+        {
+            Ok(args) => args.run(ctx),
+            Err(err) => CommandResult::from_clap(err),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Registry {
+    commands: Vec<Box<dyn RegisteredCommand>>,
+}
+
+impl Registry {
+    fn register<T: Command + 'static>(mut self) -> Self {
+        self.commands.push(Box::new(Registration::<T>(PhantomData)));
+        self
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&dyn RegisteredCommand> {
+        self.commands
+            .iter()
+            .find(|command| command.name() == name)
+            .map(Box::as_ref)
+    }
+
+    pub(crate) fn commands(&self) -> impl Iterator<Item = &dyn RegisteredCommand> {
+        self.commands.iter().map(Box::as_ref)
+    }
+}
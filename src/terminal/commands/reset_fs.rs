@@ -0,0 +1,22 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+
+/// Undo every change made to the files and directories
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct ResetFs;
+
+impl Command for ResetFs {
+    const NAME: &'static str = "reset-fs";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        ctx.state.fs_mut().reset();
+        ctx.state.update_fs();
+        let cwd = ctx.state.fs().cwd();
+        if ctx.state.env().get("PWD") != Some(&cwd) {
+            ctx.state.env_mut().change_dir(&cwd);
+        }
+        CommandResult::Output(Output::default())
+    }
+}
@@ -0,0 +1,39 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+
+/// Remove files and directories
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Rm {
+    /// Remove directories and their contents
+    #[arg(short, long, visible_short_alias = 'R')]
+    pub(crate) recursive: bool,
+    /// Ignore files that don't exist
+    #[arg(short, long)]
+    pub(crate) force: bool,
+    /// The files to remove
+    #[arg(required_unless_present = "force")]
+    pub(crate) paths: Vec<String>,
+}
+
+impl Command for Rm {
+    const NAME: &'static str = "rm";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let fs = ctx.state.fs_mut();
+        let errors = self
+            .paths
+            .iter()
+            .filter_map(|path| {
+                if self.force && !fs.exists(path) {
+                    return None;
+                }
+                fs.remove(path, self.recursive).err()
+            })
+            .collect::<Vec<_>>();
+        ctx.state.update_fs();
+
+        CommandResult::from_errors(Output::default(), errors)
+    }
+}
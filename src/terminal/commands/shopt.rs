@@ -0,0 +1,56 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+use crate::terminal::environment::{Environment, OPTIONS};
+
+/// Set and unset shell options. `failglob` makes a pattern that matches no file an error.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Shopt {
+    /// Turn the options on
+    #[arg(short, conflicts_with = "unset")]
+    pub(crate) set: bool,
+    /// Turn the options off
+    #[arg(short)]
+    pub(crate) unset: bool,
+    /// The options, all of them if none are given
+    pub(crate) names: Vec<String>,
+}
+
+pub(crate) fn shopt(args: &Shopt, env: &mut Environment) -> CommandResult {
+    let mut errors = vec![];
+    let mut names = vec![];
+    for name in &args.names {
+        match OPTIONS.iter().find(|option| *option == name) {
+            Some(option) => names.push(*option),
+            None => errors.push(format!("invalid shell option name: {}", name)),
+        }
+    }
+    if args.names.is_empty() {
+        names = OPTIONS.to_vec();
+    }
+
+    let mut stdout = String::new();
+    for name in names {
+        if (args.set || args.unset) && !args.names.is_empty() {
+            env.set_option(name, args.set);
+            continue;
+        }
+        let on = env.option(name);
+        // Without names, `-s` and `-u` list the options that are on or off
+        if (args.set && !on) || (args.unset && on) {
+            continue;
+        }
+        stdout += &format!("{:<15}\t{}\n", name, if on { "on" } else { "off" });
+    }
+
+    CommandResult::from_errors(Output::text(stdout), errors)
+}
+
+impl Command for Shopt {
+    const NAME: &'static str = "shopt";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        shopt(&self, ctx.state.env_mut())
+    }
+}
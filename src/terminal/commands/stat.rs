@@ -0,0 +1,66 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+use crate::terminal::{
+    filesystem::{Filesystem, LsResult, LsResultType},
+    time::format_full,
+};
+
+/// Print the metadata of files
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Stat {
+    /// The files to describe. Links are described themselves, not what they point to.
+    #[arg(required = true)]
+    pub(crate) paths: Vec<String>,
+}
+
+fn kind(entry: &LsResult) -> &'static str {
+    match entry.ty() {
+        LsResultType::Directory => "directory",
+        LsResultType::Link => "symbolic link",
+        LsResultType::File if entry.mime() == "inode/chardevice" => "character special file",
+        LsResultType::File if entry.size() == Some(0) => "regular empty file",
+        LsResultType::File => "regular file",
+    }
+}
+
+fn stat(path: &str, entry: &LsResult) -> String {
+    let file = match entry.target() {
+        Some(target) => format!("{} -> {}", path, target),
+        None => path.to_string(),
+    };
+    format!(
+        "  File: {}\n  Size: {:<10}  Type: {}\nAccess: ({:04o}/{})  Owner: {}  Group: {}\n  MIME: {}\nModify: {}\n",
+        file,
+        entry.size().unwrap_or(0),
+        kind(entry),
+        entry.mode(),
+        entry.permissions(),
+        entry.owner(),
+        entry.group(),
+        entry.mime(),
+        format_full(entry.modified())
+    )
+}
+
+pub(crate) fn stat_all(args: &Stat, fs: &Filesystem) -> CommandResult {
+    let mut stdout = String::new();
+    let mut errors = vec![];
+    for path in &args.paths {
+        match fs.entry(path) {
+            Ok(entry) => stdout += &stat(path, &entry),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    CommandResult::from_errors(Output::text(stdout), errors)
+}
+
+impl Command for Stat {
+    const NAME: &'static str = "stat";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        stat_all(&self, ctx.state.fs())
+    }
+}
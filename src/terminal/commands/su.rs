@@ -0,0 +1,175 @@
+use clap::Parser;
+use dioxus::{
+    prelude::consume_context,
+    signals::{Signal, Writable},
+};
+
+use super::{escape_html, Command, CommandResult, Context, Output};
+use crate::{
+    terminal::{
+        environment::Environment,
+        filesystem::{Filesystem, ROOT},
+        users::{self, User},
+    },
+    AppState,
+};
+
+/// Log in as another user
+#[derive(Parser, Debug, Clone)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Su {
+    /// Change to the home directory of the user
+    #[arg(short, long)]
+    pub(crate) login: bool,
+    /// The user to log in as
+    #[arg(default_value = ROOT)]
+    pub(crate) user: String,
+}
+
+/// Check permissions as `user` and set the prompt, `$USER` and `$HOME` for them
+fn switch(state: &mut AppState, user: &User, login: bool) {
+    let AppState {
+        filesystem, env, ..
+    } = state;
+    log_in(filesystem, env, user, login);
+    // An exported `USER` or `HOME` is saved with the new values
+    state.update_env();
+}
+
+/// Make `fs` and `env` those of `user`, in their home directory for a `login`
+fn log_in(fs: &mut Filesystem, env: &mut Environment, user: &User, login: bool) {
+    let groups = users::groups_of(fs, user)
+        .into_iter()
+        .map(|group| group.name)
+        .collect();
+    fs.login(&user.name, groups);
+    env.login(&user.name, &user.home);
+    if login && fs.cd(&user.home).is_ok() {
+        env.change_dir(&fs.cwd());
+    }
+}
+
+/// What the terminal shows of the password prompt once it is answered
+const PROMPT: &str = "Password:\n";
+
+/// Finish the `su` waiting for a password with `password`
+pub(crate) fn authenticate(password: &str) {
+    let mut state = consume_context::<Signal<AppState>>();
+    let mut state = state.write();
+    let Some(su) = state.pending_su().cloned() else {
+        return;
+    };
+    state.set_pending_su(None);
+
+    let expected = users::password(state.fs(), &su.user);
+    match users::user(state.fs(), &su.user) {
+        Some(user) if expected.as_deref() == Some(password) => {
+            switch(&mut state, &user, su.login);
+            state.set_status(0);
+            state.buffer_mut().finish_last(&escape_html(PROMPT), 0);
+        }
+        _ => {
+            state.set_status(1);
+            state.buffer_mut().finish_last(
+                &escape_html(&format!("{}authentication failure\n", PROMPT)),
+                1,
+            );
+        }
+    }
+}
+
+/// Give up on the `su` waiting for a password
+pub(crate) fn cancel() {
+    let mut state = consume_context::<Signal<AppState>>();
+    let mut state = state.write();
+    state.set_pending_su(None);
+    state.set_status(1);
+    state.buffer_mut().finish_last(&escape_html(PROMPT), 1);
+}
+
+impl Command for Su {
+    const NAME: &'static str = "su";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let fs = ctx.state.fs();
+        let Some(user) = users::user(fs, &self.user) else {
+            return CommandResult::Failed(format!("user {} does not exist", self.user));
+        };
+        // Root and accounts without a password don't need one
+        if fs.user() == ROOT || users::password(fs, &user.name).as_deref() == Some("") {
+            switch(ctx.state, &user, self.login);
+            return CommandResult::Output(Output::default());
+        }
+        // The password is only asked once the line has run, so a command after
+        // `su` would run as the old user, before `su` succeeded
+        if !ctx.last {
+            return CommandResult::Failed(
+                "su can only ask for a password as the last command of the line".to_string(),
+            );
+        }
+        // The prompt asks for the password, see `authenticate`. Until then `su` has not succeeded.
+        ctx.state.set_pending_su(Some(self));
+        CommandResult::Output(Output::default().with_status(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LocalStorageSettings;
+
+    fn session() -> (Filesystem, Environment) {
+        let settings = LocalStorageSettings::default();
+        (Filesystem::from(&settings), Environment::from(&settings))
+    }
+
+    #[test]
+    fn passwords_come_from_shadow() {
+        let (mut fs, _) = session();
+        assert_eq!(users::password(&fs, "guest").as_deref(), Some(""));
+        // A locked account can't be logged in with any password
+        assert_eq!(users::password(&fs, "admin"), None);
+
+        fs.login(ROOT, vec![]);
+        fs.write_file(users::SHADOW, "root:!\nguest:\nadmin:secret\n", false)
+            .unwrap();
+        assert_eq!(users::password(&fs, "admin").as_deref(), Some("secret"));
+        assert_eq!(users::password(&fs, "nobody"), None);
+    }
+
+    #[test]
+    fn switching_changes_user_and_home() {
+        let (mut fs, mut env) = session();
+        let cwd = fs.cwd();
+        let admin = users::user(&fs, "admin").unwrap();
+        log_in(&mut fs, &mut env, &admin, false);
+        assert_eq!(fs.user(), "admin");
+        assert_eq!(env.get("USER").map(String::as_str), Some("admin"));
+        assert_eq!(env.get("HOME").map(String::as_str), Some("/home/admin"));
+        assert_eq!(fs.cwd(), cwd);
+    }
+
+    #[test]
+    fn a_login_goes_home() {
+        let (mut fs, mut env) = session();
+        let root = users::user(&fs, ROOT).unwrap();
+        log_in(&mut fs, &mut env, &root, true);
+        assert_eq!(fs.cwd(), "/root");
+        assert_eq!(env.get("PWD").map(String::as_str), Some("/root"));
+    }
+
+    #[test]
+    fn exported_user_and_home_follow_a_switch() {
+        let (mut fs, mut env) = session();
+        env.export("HOME", "/home/guest");
+        env.export("USER", "guest");
+        let admin = users::user(&fs, "admin").unwrap();
+        log_in(&mut fs, &mut env, &admin, false);
+        let exported = env.exported();
+        assert_eq!(
+            exported.get("HOME").map(String::as_str),
+            Some("/home/admin")
+        );
+        assert_eq!(exported.get("USER").map(String::as_str), Some("admin"));
+    }
+}
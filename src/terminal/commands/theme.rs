@@ -1,6 +1,9 @@
-use clap::{command, Parser, ValueEnum};
+use clap::{Parser, ValueEnum};
 
-/// Clear the console
+use super::{Command, CommandResult, Context};
+use crate::ui::themes::{LATTE, MOCHA};
+
+/// Change the colour theme
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub(crate) struct Theme {
@@ -11,7 +14,18 @@ pub(crate) struct Theme {
 #[derive(Debug, Clone, ValueEnum)]
 pub(crate) enum ThemeName {
     Latte,
-    Frappe,
-    Macchiato,
     Mocha,
 }
+
+impl Command for Theme {
+    const NAME: &'static str = "theme";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let theme = match self.theme {
+            ThemeName::Latte => LATTE,
+            ThemeName::Mocha => MOCHA,
+        };
+        ctx.state.change_theme(theme);
+        CommandResult::empty()
+    }
+}
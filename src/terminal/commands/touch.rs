@@ -0,0 +1,27 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+
+/// Create empty files
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Touch {
+    /// The files to create. Existing files are left as they are.
+    #[arg(required = true)]
+    pub(crate) paths: Vec<String>,
+}
+
+impl Command for Touch {
+    const NAME: &'static str = "touch";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let errors = self
+            .paths
+            .iter()
+            .filter_map(|path| ctx.state.fs_mut().write_file(path, "", true).err())
+            .collect::<Vec<_>>();
+        ctx.state.update_fs();
+
+        CommandResult::from_errors(Output::default(), errors)
+    }
+}
@@ -0,0 +1,207 @@
+use clap::Parser;
+
+use super::{
+    escape_html,
+    ls::{format_name, plain_name},
+    Command, CommandResult, Context, Output,
+};
+use crate::{
+    terminal::filesystem::{Filesystem, LsResult, LsResultType},
+    ui::themes::TerminalTheme,
+};
+
+/// The note after a directory whose contents cannot be listed
+const ERROR_OPENING_DIR: &str = "  [error opening dir]";
+
+/// Show the contents of directories as a tree
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Tree {
+    /// Show entries starting with `.`
+    #[arg(short)]
+    pub(crate) all: bool,
+    /// Show directories only
+    #[arg(short)]
+    pub(crate) dirs_only: bool,
+    /// Follow links to directories
+    #[arg(short = 'l')]
+    pub(crate) follow: bool,
+    /// Descend at most this many levels
+    #[arg(short = 'L', value_name = "LEVEL")]
+    pub(crate) level: Option<usize>,
+    /// The directory to show, the current directory if not given
+    #[arg(default_value = ".")]
+    pub(crate) path: String,
+}
+
+struct Drawing<'a> {
+    theme: &'a TerminalTheme,
+    stdout: String,
+    html: String,
+    dirs: usize,
+    files: usize,
+}
+
+impl Drawing<'_> {
+    fn line(&mut self, prefix: &str, name: &str, html_name: &str, note: &str) {
+        self.stdout += &format!("{}{}{}\n", prefix, name, note);
+        self.html += &format!("{}{}{}<br />", prefix, html_name, escape_html(note));
+    }
+}
+
+impl Tree {
+    /// Draw `entries`, the contents of the directory `path`, whose ancestors are `visited`
+    fn draw_dir(
+        &self,
+        fs: &Filesystem,
+        path: &str,
+        entries: Vec<LsResult>,
+        prefix: &str,
+        visited: &mut Vec<u64>,
+        drawing: &mut Drawing,
+    ) {
+        let entries = entries
+            .into_iter()
+            .map(|entry| {
+                let entry_path = format!("{}/{}", path.trim_end_matches('/'), entry.name());
+                let followed = matches!(entry.ty(), LsResultType::Link)
+                    && self.follow
+                    && fs.is_dir(&entry_path);
+                (entry, entry_path, followed)
+            })
+            .filter(|(entry, _, _)| self.all || !entry.name().starts_with('.'))
+            .filter(|(entry, _, followed)| {
+                !self.dirs_only || *followed || matches!(entry.ty(), LsResultType::Directory)
+            })
+            .collect::<Vec<_>>();
+
+        let descend = self.level.is_none_or(|level| visited.len() < level);
+        for (idx, (entry, entry_path, followed)) in entries.iter().enumerate() {
+            let last = idx + 1 == entries.len();
+            let connector = if last { "└── " } else { "├── " };
+            let is_dir = *followed || matches!(entry.ty(), LsResultType::Directory);
+            let mut note = "";
+            let mut contents = None;
+            if is_dir {
+                drawing.dirs += 1;
+                match fs.dir_id(entry_path) {
+                    Ok(id) if visited.contains(&id) => note = "  [recursive, not followed]",
+                    Ok(_) if !descend => {}
+                    // A directory can be entered without being readable
+                    Ok(id) => match fs.list(entry_path) {
+                        Ok(entries) => contents = Some((id, entries)),
+                        Err(_) => note = ERROR_OPENING_DIR,
+                    },
+                    Err(_) => note = ERROR_OPENING_DIR,
+                }
+            } else {
+                drawing.files += 1;
+            }
+            drawing.line(
+                &format!("{}{}", prefix, connector),
+                &plain_name(entry.name(), entry.ty()),
+                &format_name(entry.name(), entry.ty(), drawing.theme),
+                note,
+            );
+
+            let Some((id, entries)) = contents else {
+                continue;
+            };
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            visited.push(id);
+            self.draw_dir(fs, entry_path, entries, &child_prefix, visited, drawing);
+            visited.pop();
+        }
+    }
+}
+
+pub(crate) fn tree(args: &Tree, fs: &Filesystem, theme: &TerminalTheme) -> CommandResult {
+    let id = match fs.dir_id(&args.path) {
+        Ok(id) => id,
+        Err(err) => return CommandResult::Failed(err),
+    };
+
+    let (entries, note) = match fs.list(&args.path) {
+        Ok(entries) => (entries, ""),
+        Err(_) => (vec![], ERROR_OPENING_DIR),
+    };
+
+    let mut drawing = Drawing {
+        theme,
+        stdout: String::new(),
+        html: String::new(),
+        dirs: 0,
+        files: 0,
+    };
+    drawing.line(
+        "",
+        &args.path,
+        &format!(
+            "<span style='{}'>{}</span>",
+            theme.peach.style_text(),
+            escape_html(&args.path)
+        ),
+        note,
+    );
+    args.draw_dir(fs, &args.path, entries, "", &mut vec![id], &mut drawing);
+
+    let plural = |count: usize, word: &str, words: &str| {
+        format!("{} {}", count, if count == 1 { word } else { words })
+    };
+    let mut summary = plural(drawing.dirs, "directory", "directories");
+    if !args.dirs_only {
+        summary += &format!(", {}", plural(drawing.files, "file", "files"));
+    }
+    CommandResult::Output(Output::html(
+        format!("{}\n{}\n", drawing.stdout, summary),
+        format!(
+            "<span style='white-space: pre'>{}<br />{}</span>",
+            drawing.html, summary
+        ),
+    ))
+}
+
+impl Command for Tree {
+    const NAME: &'static str = "tree";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        tree(&self, ctx.state.fs(), ctx.state.theme())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ui::themes::MOCHA, LocalStorageSettings};
+
+    fn tree(fs: &Filesystem, args: &[&str]) -> String {
+        let args = Tree::try_parse_from(std::iter::once(&"tree").chain(args)).unwrap();
+        match super::tree(&args, fs, &MOCHA) {
+            CommandResult::Output(output) => output.stdout,
+            res => panic!("tree failed: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn unreadable_directories_are_noted() {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.mkdir("/tmp/t/open/sub", true).unwrap();
+        fs.mkdir("/tmp/t/locked", true).unwrap();
+        fs.write_file("/tmp/t/locked/a.md", "", false).unwrap();
+        fs.chmod("/tmp/t/locked", false, |_, _| 0o311).unwrap();
+        fs.cd("/tmp/t").unwrap();
+
+        assert_eq!(
+            tree(&fs, &[]),
+            ".\n├── locked/  [error opening dir]\n└── open/\n    └── sub/\n\n3 directories, 0 files\n"
+        );
+        assert_eq!(
+            tree(&fs, &["-L", "1"]),
+            ".\n├── locked/\n└── open/\n\n2 directories, 0 files\n"
+        );
+        assert_eq!(
+            tree(&fs, &["locked"]),
+            "locked  [error opening dir]\n\n0 directories, 0 files\n"
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+use crate::terminal::alias::Aliases;
+
+/// Remove aliases
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Unalias {
+    /// Remove all aliases
+    #[arg(short)]
+    pub(crate) all: bool,
+    /// The aliases to remove
+    #[arg(required_unless_present = "all")]
+    pub(crate) names: Vec<String>,
+}
+
+pub(crate) fn unalias(args: &Unalias, aliases: &mut Aliases) -> CommandResult {
+    if args.all {
+        aliases.clear();
+    }
+
+    let errors = args
+        .names
+        .iter()
+        .filter(|name| aliases.remove(name).is_none())
+        .map(|name| format!("alias not found: {}", name))
+        .collect::<Vec<_>>();
+
+    CommandResult::from_errors(Output::default(), errors)
+}
+
+impl Command for Unalias {
+    const NAME: &'static str = "unalias";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let res = unalias(&self, ctx.state.aliases_mut());
+        ctx.state.update_aliases();
+        res
+    }
+}
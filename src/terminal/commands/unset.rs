@@ -0,0 +1,35 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+use crate::terminal::{environment::Environment, expand::is_name};
+
+/// Remove environment variables
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Unset {
+    /// The variables to remove
+    pub(crate) names: Vec<String>,
+}
+
+pub(crate) fn unset(args: &Unset, env: &mut Environment) -> CommandResult {
+    let mut errors = vec![];
+    for name in &args.names {
+        if is_name(name) {
+            env.unset(name);
+        } else {
+            errors.push(format!("not a valid identifier: {}", name));
+        }
+    }
+
+    CommandResult::from_errors(Output::default(), errors)
+}
+
+impl Command for Unset {
+    const NAME: &'static str = "unset";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        let res = unset(&self, ctx.state.env_mut());
+        ctx.state.update_env();
+        res
+    }
+}
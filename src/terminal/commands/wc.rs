@@ -0,0 +1,51 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+
+/// Count lines, words and bytes of the input
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Wc {
+    /// Print the line count
+    #[arg(short, long)]
+    pub(crate) lines: bool,
+    /// Print the word count
+    #[arg(short, long)]
+    pub(crate) words: bool,
+    /// Print the byte count
+    #[arg(short = 'c', long)]
+    pub(crate) bytes: bool,
+}
+
+pub(crate) fn wc(args: &Wc, input: &str) -> String {
+    let all = !args.lines && !args.words && !args.bytes;
+    let mut counts = vec![];
+    if all || args.lines {
+        counts.push(input.matches('\n').count());
+    }
+    if all || args.words {
+        counts.push(input.split_whitespace().count());
+    }
+    if all || args.bytes {
+        counts.push(input.len());
+    }
+
+    if counts.len() == 1 {
+        format!("{}\n", counts[0])
+    } else {
+        counts
+            .iter()
+            .map(|count| format!("{:>7}", count))
+            .collect::<Vec<_>>()
+            .join(" ")
+            + "\n"
+    }
+}
+
+impl Command for Wc {
+    const NAME: &'static str = "wc";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        CommandResult::Output(Output::text(wc(&self, &ctx.stdin)))
+    }
+}
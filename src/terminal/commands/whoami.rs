@@ -0,0 +1,16 @@
+use clap::Parser;
+
+use super::{Command, CommandResult, Context, Output};
+
+/// Print the name of the current user
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Whoami;
+
+impl Command for Whoami {
+    const NAME: &'static str = "whoami";
+
+    fn run(self, ctx: &mut Context) -> CommandResult {
+        CommandResult::Output(Output::text(format!("{}\n", ctx.state.fs().user())))
+    }
+}
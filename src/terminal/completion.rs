@@ -0,0 +1,338 @@
+use super::{
+    alias::Aliases,
+    commands::registry::registry,
+    environment::Environment,
+    filesystem::Filesystem,
+    lexer::{self, Token},
+};
+
+/// The result of completing the last word of a command line
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Completion {
+    /// The line with the last word completed as far as it is unambiguous
+    pub(crate) line: String,
+    /// The possible completions, to be listed when there is more than one
+    pub(crate) candidates: Vec<String>,
+}
+
+struct Candidate {
+    /// The whole word to insert
+    value: String,
+    /// How the candidate is listed, e.g. the file name without its directory
+    display: String,
+}
+
+/// Complete the last word of `line`: a command name in command position,
+/// a flag of the current command if the word starts with `-`, a path otherwise.
+pub(crate) fn complete(
+    line: &str,
+    fs: &Filesystem,
+    env: &Environment,
+    aliases: &Aliases,
+) -> Completion {
+    let (before, word) = line.split_at(word_start(line));
+    let word = unescape(word);
+    let candidates = candidates(before, &word, fs, env, aliases);
+
+    let mut completion = Completion {
+        line: line.to_string(),
+        candidates: vec![],
+    };
+    match candidates.as_slice() {
+        [] => {}
+        [candidate] => {
+            let suffix = if candidate.value.ends_with('/') {
+                ""
+            } else {
+                " "
+            };
+            completion.line = format!("{}{}{}", before, escape(&candidate.value), suffix);
+        }
+        candidates => {
+            let prefix = common_prefix(candidates.iter().map(|candidate| &candidate.value));
+            if prefix.len() > word.len() {
+                completion.line = format!("{}{}", before, escape(&prefix));
+            }
+            completion.candidates = candidates
+                .iter()
+                .map(|candidate| candidate.display.clone())
+                .collect();
+        }
+    }
+    completion
+}
+
+/// Suggest a whole line for `line` from the commands and the filesystem, using
+/// the first possible completion of the last word
+pub(crate) fn suggest(
+    line: &str,
+    fs: &Filesystem,
+    env: &Environment,
+    aliases: &Aliases,
+) -> Option<String> {
+    let (before, word) = line.split_at(word_start(line));
+    if word.is_empty() {
+        return None;
+    }
+    candidates(before, &unescape(word), fs, env, aliases)
+        .first()
+        .map(|candidate| format!("{}{}", before, escape(&candidate.value)))
+        .filter(|suggestion| suggestion.len() > line.len() && suggestion.starts_with(line))
+}
+
+fn candidates(
+    before: &str,
+    word: &str,
+    fs: &Filesystem,
+    env: &Environment,
+    aliases: &Aliases,
+) -> Vec<Candidate> {
+    match command_name(before) {
+        None => commands(word, aliases),
+        Some(name) if word.starts_with('-') => flags(&name, word),
+        Some(_) => paths(word, fs, env.get("HOME").map(String::as_str)),
+    }
+}
+
+/// The byte offset where the last word of `line` starts
+fn word_start(line: &str) -> usize {
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (idx, chr) in line.char_indices() {
+        match (quote, chr) {
+            _ if escaped => escaped = false,
+            (Some(open), chr) if chr == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\\') => escaped = true,
+            (None, '\'' | '"') => quote = Some(chr),
+            (None, chr) if chr.is_whitespace() || "|&;<>".contains(chr) => {
+                start = idx + chr.len_utf8()
+            }
+            _ => {}
+        }
+    }
+    start
+}
+
+/// The name of the command the word after `before` belongs to, `None` in command position
+fn command_name(before: &str) -> Option<String> {
+    let tokens = lexer::tokenize(before).ok()?;
+    let mut name = None;
+    let mut redirect_target = false;
+    for token in tokens {
+        match token {
+            Token::Word(word) if name.is_none() && !redirect_target => {
+                name = Some(word.parts().iter().map(|part| part.text()).collect());
+            }
+            Token::Word(_) => redirect_target = false,
+            Token::Great | Token::DGreat | Token::Less => redirect_target = true,
+            Token::Pipe | Token::Semi | Token::AndIf | Token::OrIf | Token::Amp => name = None,
+        }
+    }
+    // A pending redirection target is completed as a path
+    if redirect_target {
+        return Some(name.unwrap_or_default());
+    }
+    name
+}
+
+fn commands(word: &str, aliases: &Aliases) -> Vec<Candidate> {
+    let mut names = registry()
+        .commands()
+        .map(|command| command.name().to_string())
+        .chain(aliases.entries().keys().cloned())
+        .filter(|name| name.starts_with(word))
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .map(|name| Candidate {
+            value: name.clone(),
+            display: name,
+        })
+        .collect()
+}
+
+fn flags(command: &str, word: &str) -> Vec<Candidate> {
+    let Some(command) = registry().get(command) else {
+        return vec![];
+    };
+    let mut parser = command.parser();
+    parser.build();
+    let mut flags = parser
+        .get_arguments()
+        .flat_map(|arg| {
+            let long = arg.get_long().map(|long| format!("--{}", long));
+            let short = arg.get_short().map(|short| format!("-{}", short));
+            long.into_iter().chain(short)
+        })
+        .filter(|flag| flag.starts_with(word))
+        .collect::<Vec<_>>();
+    flags.sort();
+    flags
+        .into_iter()
+        .map(|flag| Candidate {
+            value: flag.clone(),
+            display: flag,
+        })
+        .collect()
+}
+
+/// The paths starting with `word`. A leading `~` stands for `home`, as the
+/// shell expands it, and is kept in the completed word.
+fn paths(word: &str, fs: &Filesystem, home: Option<&str>) -> Vec<Candidate> {
+    let (dir, prefix) = match word.rsplit_once('/') {
+        Some(("", prefix)) => ("/".to_string(), prefix),
+        Some((dir, prefix)) => (format!("{}/", dir), prefix),
+        None => ("".to_string(), word),
+    };
+    let expanded = match (dir.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => format!("{}/{}", home, rest),
+        _ => dir.clone(),
+    };
+    let Ok(entries) = fs.list(if expanded.is_empty() { "." } else { &expanded }) else {
+        return vec![];
+    };
+    entries
+        .iter()
+        .filter(|entry| entry.name().starts_with(prefix))
+        .filter(|entry| prefix.starts_with('.') || !entry.name().starts_with('.'))
+        .map(|entry| {
+            // Links to directories get a `/` too, so that the next Tab descends into them
+            let display = if fs.is_dir(&format!("{}{}", expanded, entry.name())) {
+                format!("{}/", entry.name())
+            } else {
+                entry.name().to_string()
+            };
+            Candidate {
+                value: format!("{}{}", dir, display),
+                display,
+            }
+        })
+        .collect()
+}
+
+fn common_prefix<'a>(mut values: impl Iterator<Item = &'a String>) -> String {
+    let Some(first) = values.next() else {
+        return "".to_string();
+    };
+    let mut len = first.len();
+    for value in values {
+        len = first
+            .char_indices()
+            .zip(value.chars())
+            .take_while(|((idx, a), b)| *idx < len && a == b)
+            .map(|((idx, a), _)| idx + a.len_utf8())
+            .last()
+            .unwrap_or(0);
+    }
+    first[..len].to_string()
+}
+
+/// Remove the quotes and backslashes of a (possibly unfinished) word
+fn unescape(word: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = word.chars();
+    while let Some(chr) = chars.next() {
+        match chr {
+            '\\' => unescaped.extend(chars.next()),
+            '\'' | '"' => {}
+            chr => unescaped.push(chr),
+        }
+    }
+    unescaped
+}
+
+/// `word` with a backslash before every character the shell would interpret
+pub(crate) fn escape(word: &str) -> String {
+    let mut escaped = String::new();
+    for chr in word.chars() {
+        if chr.is_whitespace() || "'\"\\|&;<>()$`#*?[]".contains(chr) {
+            escaped.push('\\');
+        }
+        escaped.push(chr);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LocalStorageSettings;
+
+    /// A filesystem with `notes.md`, `new dir/` and a link `docs` to `new dir`
+    /// in `/tmp/c`, which is the working directory
+    fn filesystem() -> Filesystem {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.mkdir("/tmp/c/new dir", true).unwrap();
+        fs.write_file("/tmp/c/notes.md", "", false).unwrap();
+        fs.write_file("/tmp/c/.hidden", "", false).unwrap();
+        fs.symlink("new dir", "/tmp/c/docs", false).unwrap();
+        fs.cd("/tmp/c").unwrap();
+        fs
+    }
+
+    fn complete(line: &str) -> Completion {
+        let settings = LocalStorageSettings::default();
+        let env = Environment::from(&settings);
+        super::complete(line, &filesystem(), &env, &Aliases::from(&settings))
+    }
+
+    #[test]
+    fn commands_complete_in_command_position() {
+        assert_eq!(complete("whoa").line, "whoami ");
+        assert_eq!(complete("ls | whoa").line, "ls | whoami ");
+        let completion = complete("c");
+        assert!(completion.candidates.contains(&"cat".to_string()));
+        assert!(completion.candidates.contains(&"cd".to_string()));
+    }
+
+    #[test]
+    fn flags_complete_after_a_dash() {
+        assert_eq!(complete("cat --r").line, "cat --raw ");
+    }
+
+    #[test]
+    fn directories_complete_with_a_slash() {
+        assert_eq!(complete("cd ne").line, "cd new\\ dir/");
+        assert_eq!(complete("cd do").line, "cd docs/");
+        assert_eq!(complete("cat no").line, "cat notes.md ");
+        assert_eq!(complete("ls /tmp/c/n").candidates, ["new dir/", "notes.md"]);
+        assert_eq!(complete("ls /tmp/c/n").line, "ls /tmp/c/n");
+    }
+
+    #[test]
+    fn hidden_files_need_a_dot() {
+        assert_eq!(
+            complete("cat ").candidates,
+            ["docs/", "new dir/", "notes.md"]
+        );
+        assert_eq!(complete("cat .h").line, "cat .hidden ");
+    }
+
+    #[test]
+    fn suggestions_complete_the_line() {
+        let settings = LocalStorageSettings::default();
+        let (fs, env, aliases) = (
+            filesystem(),
+            Environment::from(&settings),
+            Aliases::from(&settings),
+        );
+        assert_eq!(
+            suggest("cat no", &fs, &env, &aliases).as_deref(),
+            Some("cat notes.md")
+        );
+        assert_eq!(suggest("cat ", &fs, &env, &aliases), None);
+        assert_eq!(suggest("cat x", &fs, &env, &aliases), None);
+    }
+
+    #[test]
+    fn a_leading_tilde_completes_from_the_home() {
+        assert_eq!(complete("cd ~/pro").line, "cd ~/projects/");
+        assert!(complete("cat ~/")
+            .candidates
+            .contains(&"projects/".to_string()));
+    }
+}
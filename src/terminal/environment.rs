@@ -0,0 +1,91 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::LocalStorageSettings;
+
+pub(crate) const HOME: &str = "/home/guest";
+/// The user every session starts as
+pub(crate) const USER: &str = "guest";
+/// The shell options `shopt` knows about
+pub(crate) const OPTIONS: &[&str] = &["failglob"];
+
+/// The shell variables. The names of the variables set with `export` are
+/// remembered separately so that they can be saved in the local storage.
+#[derive(Debug, Clone)]
+pub(crate) struct Environment {
+    vars: BTreeMap<String, String>,
+    exported: BTreeSet<String>,
+    /// The shell options that are on, only kept for the session
+    options: BTreeSet<String>,
+}
+
+impl From<&LocalStorageSettings> for Environment {
+    fn from(value: &LocalStorageSettings) -> Self {
+        let mut vars = BTreeMap::new();
+        vars.insert("SHELL".to_string(), "/bin/sh".to_string());
+        vars.extend(value.env.clone());
+        // Every session starts as the guest in their home directory, whatever was exported before
+        vars.insert("HOME".to_string(), HOME.to_string());
+        vars.insert("USER".to_string(), USER.to_string());
+        vars.insert("PWD".to_string(), HOME.to_string());
+        vars.insert("OLDPWD".to_string(), HOME.to_string());
+        Self {
+            vars,
+            exported: value.env.keys().cloned().collect(),
+            options: BTreeSet::new(),
+        }
+    }
+}
+
+impl Environment {
+    pub(crate) fn get(&self, name: &str) -> Option<&String> {
+        self.vars.get(name)
+    }
+
+    pub(crate) fn export(&mut self, name: &str, value: &str) {
+        self.vars.insert(name.to_string(), value.to_string());
+        self.exported.insert(name.to_string());
+    }
+
+    pub(crate) fn unset(&mut self, name: &str) {
+        self.vars.remove(name);
+        self.exported.remove(name);
+    }
+
+    /// Set `USER` and `HOME` for the user logged in with `su`
+    pub(crate) fn login(&mut self, user: &str, home: &str) {
+        self.vars.insert("USER".to_string(), user.to_string());
+        self.vars.insert("HOME".to_string(), home.to_string());
+    }
+
+    /// Keep `PWD` and `OLDPWD` in step with the filesystem after a `cd`
+    pub(crate) fn change_dir(&mut self, cwd: &str) {
+        let old = self.vars.insert("PWD".to_string(), cwd.to_string());
+        self.vars
+            .insert("OLDPWD".to_string(), old.unwrap_or_default());
+    }
+
+    pub(crate) fn vars(&self) -> &BTreeMap<String, String> {
+        &self.vars
+    }
+
+    /// The exported variables with their current values
+    pub(crate) fn exported(&self) -> BTreeMap<String, String> {
+        self.vars
+            .iter()
+            .filter(|(name, _)| self.exported.contains(*name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    pub(crate) fn option(&self, name: &str) -> bool {
+        self.options.contains(name)
+    }
+
+    pub(crate) fn set_option(&mut self, name: &str, on: bool) {
+        if on {
+            self.options.insert(name.to_string());
+        } else {
+            self.options.remove(name);
+        }
+    }
+}
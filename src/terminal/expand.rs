@@ -0,0 +1,342 @@
+use std::{iter::Peekable, str::Chars};
+
+use super::lexer::{Word, WordPart};
+
+/// A field of an expanded word
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Field {
+    pub(crate) text: String,
+    /// The field as a glob pattern with its quoted characters escaped, if it
+    /// has an unquoted `*`, `?` or `[`
+    pub(crate) pattern: Option<String>,
+}
+
+/// Builds the fields a word expands to. Only the results of unquoted expansions
+/// are split on whitespace, literal text and quoted parts never are.
+#[derive(Default)]
+struct Fields {
+    fields: Vec<Field>,
+    current: String,
+    pattern: String,
+    /// Whether `current` has an unquoted glob character
+    glob: bool,
+    /// Whether `current` is a field even if it is empty, as for `""`
+    started: bool,
+}
+
+impl Fields {
+    fn push_quoted(&mut self, text: &str) {
+        self.current.push_str(text);
+        for chr in text.chars() {
+            if "*?[]\\".contains(chr) {
+                self.pattern.push('\\');
+            }
+            self.pattern.push(chr);
+        }
+        self.started = true;
+    }
+
+    fn push_str(&mut self, text: &str) {
+        self.current.push_str(text);
+        self.pattern.push_str(text);
+        self.glob |= text.contains(['*', '?', '[']);
+        self.started = true;
+    }
+
+    fn push_split(&mut self, text: &str) {
+        let mut pieces = text.split_whitespace().peekable();
+        if text.starts_with(char::is_whitespace) {
+            self.finish();
+        }
+        while let Some(piece) = pieces.next() {
+            self.push_str(piece);
+            if pieces.peek().is_some() {
+                self.finish();
+            }
+        }
+        if text.ends_with(char::is_whitespace) {
+            self.finish();
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.started {
+            let pattern = std::mem::take(&mut self.pattern);
+            self.fields.push(Field {
+                text: std::mem::take(&mut self.current),
+                pattern: self.glob.then_some(pattern),
+            });
+            self.glob = false;
+            self.started = false;
+        }
+    }
+
+    fn into_fields(mut self) -> Vec<Field> {
+        self.finish();
+        self.fields
+    }
+}
+
+/// Expand the parameters in the unquoted and double quoted parts of a word and
+/// split the result into fields. `lookup` resolves a parameter name like `HOME` or `?`.
+pub(crate) fn expand_word<F>(word: &Word, lookup: &F) -> Result<Vec<String>, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    Ok(expand_fields(word, lookup)?
+        .into_iter()
+        .map(|field| field.text)
+        .collect())
+}
+
+/// [`expand_word`], keeping track of the glob characters that were not quoted
+pub(crate) fn expand_fields<F>(word: &Word, lookup: &F) -> Result<Vec<Field>, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut fields = Fields::default();
+    let mut parts = word.parts();
+    if let Some((WordPart::Bare(text), rest)) = parts.split_first() {
+        // A leading `~` is the home directory when it is unquoted and alone
+        // or before a `/`. The home itself is never split or globbed.
+        let tilde = text == "~" && rest.is_empty() || text.starts_with("~/");
+        if let Some(home) = lookup("HOME").filter(|_| tilde) {
+            fields.push_quoted(&home);
+            expand_bare(&text[1..], &mut fields, lookup)?;
+            parts = rest;
+        }
+    }
+    for part in parts {
+        match part {
+            WordPart::Single(text) => fields.push_quoted(text),
+            WordPart::Double(text) => fields.push_quoted(&expand_str(text, lookup)?),
+            WordPart::Bare(text) => expand_bare(text, &mut fields, lookup)?,
+        }
+    }
+    Ok(fields.into_fields())
+}
+
+fn expand_bare<F>(text: &str, fields: &mut Fields, lookup: &F) -> Result<(), String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    for segment in expand_text(text, lookup)? {
+        match segment {
+            Segment::Literal(literal) if literal.is_empty() => {}
+            Segment::Literal(literal) => fields.push_str(&literal),
+            Segment::Value(value) => fields.push_split(&value),
+        }
+    }
+    Ok(())
+}
+
+/// Expand a string as if it was double quoted
+pub(crate) fn expand_str<F>(text: &str, lookup: &F) -> Result<String, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    Ok(expand_text(text, lookup)?
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Literal(text) | Segment::Value(text) => text,
+        })
+        .collect())
+}
+
+enum Segment {
+    Literal(String),
+    Value(String),
+}
+
+fn expand_text<F>(text: &str, lookup: &F) -> Result<Vec<Segment>, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut segments = vec![];
+    let mut literal = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(chr) = chars.next() {
+        if chr != '$' {
+            literal.push(chr);
+            continue;
+        }
+        let value = match chars.peek() {
+            Some('{') => {
+                chars.next();
+                let mut param = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(chr) => param.push(chr),
+                        None => return Err(format!("bad substitution: ${{{}", param)),
+                    }
+                }
+                expand_braced(&param, lookup)?
+            }
+            Some('?') => {
+                chars.next();
+                lookup("?").unwrap_or_default()
+            }
+            Some(chr) if is_name_start(*chr) => lookup(&read_name(&mut chars)).unwrap_or_default(),
+            _ => {
+                literal.push('$');
+                continue;
+            }
+        };
+        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+        segments.push(Segment::Value(value));
+    }
+    segments.push(Segment::Literal(literal));
+    Ok(segments)
+}
+
+/// The inside of `${...}`: either `NAME` or `NAME:-default`
+fn expand_braced<F>(param: &str, lookup: &F) -> Result<String, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let (name, default) = match param.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (param, None),
+    };
+    if name != "?" && !is_name(name) {
+        return Err(format!("bad substitution: ${{{}}}", param));
+    }
+    match (lookup(name).filter(|value| !value.is_empty()), default) {
+        (Some(value), _) => Ok(value),
+        (None, Some(default)) => expand_str(default, lookup),
+        (None, None) => Ok("".to_string()),
+    }
+}
+
+fn read_name(chars: &mut Peekable<Chars>) -> String {
+    let mut name = String::new();
+    while let Some(chr) = chars.next_if(|chr| chr.is_ascii_alphanumeric() || *chr == '_') {
+        name.push(chr);
+    }
+    name
+}
+
+fn is_name_start(chr: char) -> bool {
+    chr.is_ascii_alphabetic() || chr == '_'
+}
+
+/// Whether `name` is a valid variable name
+pub(crate) fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_name_start)
+        && chars.all(|chr| chr.is_ascii_alphanumeric() || chr == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::terminal::lexer::{tokenize, Token};
+
+    fn word(input: &str) -> Word {
+        match tokenize(input).unwrap().remove(0) {
+            Token::Word(word) => word,
+            token => panic!("not a word: {:?}", token),
+        }
+    }
+
+    fn vars() -> HashMap<&'static str, &'static str> {
+        HashMap::from([
+            ("A", "a  b"),
+            ("EMPTY", ""),
+            ("X", "x"),
+            ("?", "1"),
+            ("HOME", "/home/a b*"),
+        ])
+    }
+
+    fn expand(input: &str) -> Result<Vec<String>, String> {
+        let vars = vars();
+        expand_word(&word(input), &|name| {
+            vars.get(name).map(|value| value.to_string())
+        })
+    }
+
+    #[test]
+    fn unquoted_values_are_split() {
+        assert_eq!(expand("$A").unwrap(), ["a", "b"]);
+        assert_eq!(expand("x${A}y").unwrap(), ["xa", "by"]);
+        assert_eq!(expand("\"$A\"").unwrap(), ["a  b"]);
+        assert_eq!(expand("'$A'").unwrap(), ["$A"]);
+        assert_eq!(expand("$X$X").unwrap(), ["xx"]);
+    }
+
+    #[test]
+    fn empty_values_vanish_unless_quoted() {
+        assert!(expand("$EMPTY").unwrap().is_empty());
+        assert!(expand("$UNSET").unwrap().is_empty());
+        assert_eq!(expand("\"$EMPTY\"").unwrap(), [""]);
+        assert_eq!(expand("''").unwrap(), [""]);
+    }
+
+    #[test]
+    fn braced_parameters_have_defaults() {
+        assert_eq!(expand("${X:-d}").unwrap(), ["x"]);
+        assert_eq!(expand("${UNSET:-d}").unwrap(), ["d"]);
+        assert_eq!(expand("${EMPTY:-d}").unwrap(), ["d"]);
+        assert_eq!(expand("${UNSET:-$X}").unwrap(), ["x"]);
+        assert_eq!(expand("\"${UNSET:-a b}\"").unwrap(), ["a b"]);
+        assert!(expand("${UNSET}").unwrap().is_empty());
+        assert_eq!(expand("$?").unwrap(), ["1"]);
+        assert_eq!(expand("${?}").unwrap(), ["1"]);
+    }
+
+    #[test]
+    fn bad_substitutions_are_errors() {
+        assert!(expand("${1A}").is_err());
+        assert!(expand("${A").is_err());
+        assert!(expand("${}").is_err());
+    }
+
+    #[test]
+    fn a_lone_dollar_is_literal() {
+        assert_eq!(expand("$").unwrap(), ["$"]);
+        assert_eq!(expand("a$1").unwrap(), ["a$1"]);
+    }
+
+    #[test]
+    fn only_unquoted_glob_characters_make_a_pattern() {
+        let pattern = |input| {
+            let vars = vars();
+            let fields = expand_fields(&word(input), &|name| {
+                vars.get(name).map(|value| value.to_string())
+            })
+            .unwrap();
+            fields[0].pattern.clone()
+        };
+        assert_eq!(pattern("*.md"), Some("*.md".to_string()));
+        assert_eq!(pattern("'*.md'"), None);
+        assert_eq!(pattern("\\*.md"), None);
+        assert_eq!(pattern("\"a*\"*"), Some("a\\**".to_string()));
+        assert_eq!(pattern("'['?"), Some("\\[?".to_string()));
+        assert_eq!(pattern("$X"), None);
+    }
+
+    #[test]
+    fn a_leading_unquoted_tilde_is_the_home() {
+        assert_eq!(expand("~").unwrap(), ["/home/a b*"]);
+        assert_eq!(expand("~/$X").unwrap(), ["/home/a b*/x"]);
+        assert_eq!(expand("~/'c d'").unwrap(), ["/home/a b*/c d"]);
+        assert_eq!(expand("\\~").unwrap(), ["~"]);
+        assert_eq!(expand("'~'/a").unwrap(), ["~/a"]);
+        assert_eq!(expand("~\"/a\"").unwrap(), ["~/a"]);
+        assert_eq!(expand("a~").unwrap(), ["a~"]);
+        assert_eq!(expand("~a").unwrap(), ["~a"]);
+    }
+
+    #[test]
+    fn the_home_is_not_a_pattern() {
+        let fields = expand_fields(&word("~/*"), &|name| {
+            (name == "HOME").then(|| "/h*".to_string())
+        })
+        .unwrap();
+        assert_eq!(fields[0].pattern, Some("/h\\*/*".to_string()));
+    }
+}
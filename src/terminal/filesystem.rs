@@ -1,9 +1,25 @@
-use std::sync::atomic::AtomicU64;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    sync::atomic::AtomicU64,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use anyhow::{Context, Result};
 use lazy_static::lazy_static;
 
-use crate::projects;
+use super::{
+    environment::{HOME, USER},
+    overlay::{Attributes, Overlay, OverlayEntry},
+    path::VirtualPath,
+    system::{self, Dynamic},
+    users,
+};
+use crate::{
+    content::{mime_type, Content, CONTENT},
+    LocalStorageSettings,
+};
 
 lazy_static! {
     static ref NEXT_NODE_ID: AtomicU64 = AtomicU64::new(0);
@@ -13,206 +29,992 @@ pub(crate) fn next_node_id() -> u64 {
     NEXT_NODE_ID.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
 }
 
+/// How many links are followed in total to resolve a path before giving up
+const MAX_LINKS: usize = 40;
+
+/// The error when resolving a path takes more than [`MAX_LINKS`] links
+const TOO_MANY_LINKS: &str = "too many levels of symbolic links";
+
+/// The permission bits of one class of users, as in `chmod`
+pub(crate) const READ: u32 = 0o4;
+pub(crate) const WRITE: u32 = 0o2;
+pub(crate) const EXECUTE: u32 = 0o1;
+
+/// The user that every permission check lets through
+pub(crate) const ROOT: &str = "root";
+
+/// Seconds since the Unix epoch
+pub(crate) fn now() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    return (web_sys::js_sys::Date::now() / 1000.0) as u64;
+    #[cfg(not(target_arch = "wasm32"))]
+    return SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+}
+
 #[derive(Debug, Clone)]
 pub(crate) enum FsEntryType {
-    Directory(Vec<FsEntry>),
+    /// The ids of the children, by name
+    Directory(BTreeMap<String, u64>),
     File(String),
-    Link(u64),
+    /// The path the link points to, as it was given
+    Link(String),
+    /// A file whose contents are computed when it is read
+    Dynamic(Dynamic),
 }
 
 #[derive(Debug, Clone)]
 pub(crate) struct FsEntry {
     id: u64,
-    name: &'static str,
-    full_path: &'static str,
-    accessible: bool,
+    name: String,
+    /// `None` only for the root
+    parent: Option<u64>,
+    /// Whether the node is part of the built-in content, at this path
+    builtin: bool,
+    /// The permission bits, like `0o755`
+    mode: u32,
+    /// The last modification, in seconds since the Unix epoch
+    modified: u64,
+    owner: String,
+    group: String,
+    mime: &'static str,
     ty: FsEntryType,
 }
 
-impl FsEntry {
-    pub(crate) fn new(
-        name: &'static str,
-        full_path: &'static str,
-        accessible: bool,
-        ty: FsEntryType,
-    ) -> Self {
-        Self {
-            id: next_node_id(),
-            name,
-            full_path,
-            accessible,
-            ty,
-        }
-    }
-
-    pub(crate) fn new_dir(full_path: &'static str, children: Vec<FsEntry>) -> Self {
-        let name = full_path
-            .split("/")
-            .last()
-            .expect("Malformed path. Fix it!");
-        Self {
-            id: next_node_id(),
-            name,
-            full_path,
-            accessible: true,
-            ty: FsEntryType::Directory(children),
-        }
-    }
-}
-
+/// The nodes of the tree are stored flat by id. Directories index their
+/// children by name and every node links to its parent, so lookups never scan the tree.
 #[derive(Debug, Clone)]
 pub(crate) struct Filesystem {
-    root: FsEntry,
-    cwd: u64,
+    nodes: HashMap<u64, FsEntry>,
+    root: u64,
+    cwd: VirtualPath,
+    /// Who owns the nodes created by the user, and whose permissions are checked
+    user: String,
+    /// The groups of the user. The first one is the group of the nodes they create.
+    groups: Vec<String>,
+    overlay: Overlay,
+}
+
+impl From<&LocalStorageSettings> for Filesystem {
+    fn from(value: &LocalStorageSettings) -> Self {
+        let mut fs = Self::builtin();
+        fs.apply(value.filesystem.clone());
+        fs
+    }
 }
 
 impl Filesystem {
-    pub(crate) fn new() -> Self {
+    /// The filesystem without any change made by the user
+    fn builtin() -> Self {
+        let root = next_node_id();
         let mut fs = Self {
-            root: FsEntry::new(
-                "/",
-                "/",
-                true,
-                FsEntryType::Directory(vec![FsEntry::new_dir(
-                    "/home",
-                    vec![FsEntry::new_dir(
-                        "/home/user",
-                        vec![projects::gen_project_directory()],
-                    )],
-                )]),
-            ),
-            cwd: 0,
+            nodes: HashMap::from([(
+                root,
+                FsEntry {
+                    id: root,
+                    name: "/".to_string(),
+                    parent: None,
+                    builtin: true,
+                    mode: 0o755,
+                    modified: now(),
+                    owner: ROOT.to_string(),
+                    group: ROOT.to_string(),
+                    mime: "inode/directory",
+                    ty: FsEntryType::Directory(BTreeMap::new()),
+                },
+            )]),
+            root,
+            cwd: VirtualPath::root(),
+            // The content is added whatever its permissions
+            user: ROOT.to_string(),
+            groups: vec![],
+            overlay: Overlay::default(),
         };
-        let home_id = Self::find(&fs.root, |node| node.full_path == "/home/user");
-        fs.cwd = home_id.expect("Hardcoded directories should exist").id;
+
+        // The paths are sorted, so every directory comes before its contents
+        for (path, metadata, content) in CONTENT {
+            let path = fs.path(&format!("/{}", path));
+            let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+                continue;
+            };
+            let dir = fs
+                .resolve_dir(&parent, "")
+                .expect("Bundled content is inside a directory")
+                .id;
+            let id = match content {
+                Content::Directory => fs.add_dir(dir, name),
+                Content::File(contents) => {
+                    fs.insert(dir, name, FsEntryType::File(contents.to_string()))
+                }
+                Content::Link(target) => {
+                    fs.insert(dir, name, FsEntryType::Link(target.to_string()))
+                }
+            };
+            let node = fs.node_mut(id);
+            node.mode = metadata.mode;
+            node.modified = metadata.modified;
+            node.owner = metadata.owner.to_string();
+            node.group = metadata.group.to_string();
+            node.mime = metadata.mime;
+        }
+        fs.add_system();
+
+        for node in fs.nodes.values_mut() {
+            node.builtin = true;
+        }
+        let groups = users::user(&fs, USER)
+            .map(|user| users::groups_of(&fs, &user))
+            .unwrap_or_default();
+        fs.login(USER, groups.into_iter().map(|group| group.name).collect());
+        fs.cwd = fs.path(HOME);
         fs
     }
 
-    fn find<'a, F>(root: &'a FsEntry, func: F) -> Option<&'a FsEntry>
-    where
-        F: Fn(&'a FsEntry) -> bool + Clone,
-    {
-        if func(&root) {
-            return Some(&root);
+    /// Add the system directories that are missing from the content, and the dynamic files
+    fn add_system(&mut self) {
+        for &(path, mode) in system::DIRECTORIES {
+            let name = path.trim_start_matches('/');
+            if self.child(self.root, name).is_none() {
+                let id = self.add_dir(self.root, name);
+                self.node_mut(id).mode = mode;
+            }
+        }
+        for &(path, kind, mode) in system::FILES {
+            let path = self.path(path);
+            let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+                continue;
+            };
+            let dir = self
+                .resolve_dir(&parent, "")
+                .expect("System files are inside a system directory")
+                .id;
+            let id = self.insert(dir, name, FsEntryType::Dynamic(kind));
+            self.node_mut(id).mode = mode;
         }
+    }
 
-        if let FsEntryType::Directory(children) = &root.ty {
-            for node in children {
-                if let Some(node) = Self::find(node, func.clone()) {
-                    return Some(node);
-                }
+    /// Check permissions as `user` from now on. Without `groups`, the user
+    /// only belongs to the group named like them.
+    pub(crate) fn login(&mut self, user: &str, mut groups: Vec<String>) {
+        if groups.is_empty() {
+            groups.push(user.to_string());
+        }
+        self.user = user.to_string();
+        self.groups = groups;
+    }
+
+    /// The user the permissions are checked for
+    pub(crate) fn user(&self) -> &str {
+        &self.user
+    }
+
+    fn node(&self, id: u64) -> &FsEntry {
+        self.nodes
+            .get(&id)
+            .expect("Node ids always refer to a node")
+    }
+
+    fn node_mut(&mut self, id: u64) -> &mut FsEntry {
+        self.nodes
+            .get_mut(&id)
+            .expect("Node ids always refer to a node")
+    }
+
+    /// Add a node named `name` to the directory `parent` and return its id. It
+    /// belongs to the user and gets the usual mode for its type.
+    fn insert(&mut self, parent: u64, name: &str, ty: FsEntryType) -> u64 {
+        let id = next_node_id();
+        let group = self.groups.first().unwrap_or(&self.user).clone();
+        let (mode, mime) = match &ty {
+            FsEntryType::Directory(_) => (0o755, "inode/directory"),
+            FsEntryType::File(contents) => (0o644, mime_type(name, contents)),
+            FsEntryType::Link(_) => (0o777, "inode/symlink"),
+            FsEntryType::Dynamic(kind) if kind.is_device() => (0o666, "inode/chardevice"),
+            FsEntryType::Dynamic(kind) => (0o644, mime_type(name, &kind.contents())),
+        };
+        self.nodes.insert(
+            id,
+            FsEntry {
+                id,
+                name: name.to_string(),
+                parent: Some(parent),
+                builtin: false,
+                mode,
+                modified: now(),
+                owner: self.user.clone(),
+                group,
+                mime,
+                ty,
+            },
+        );
+        if let FsEntryType::Directory(children) = &mut self.node_mut(parent).ty {
+            children.insert(name.to_string(), id);
+        }
+        id
+    }
+
+    fn add_dir(&mut self, parent: u64, name: &str) -> u64 {
+        self.insert(parent, name, FsEntryType::Directory(BTreeMap::new()))
+    }
+
+    /// Whether the user may access `entry` in every way of `access`, a
+    /// combination of [`READ`], [`WRITE`] and [`EXECUTE`]. Root may do anything.
+    fn allowed(&self, entry: &FsEntry, access: u32) -> bool {
+        if self.user == ROOT {
+            return true;
+        }
+        let shift = if entry.owner == self.user {
+            6
+        } else if self.groups.contains(&entry.group) {
+            3
+        } else {
+            0
+        };
+        (entry.mode >> shift) & access == access
+    }
+
+    fn check(&self, entry: &FsEntry, access: u32, display: &str) -> Result<(), String> {
+        if self.allowed(entry, access) {
+            Ok(())
+        } else {
+            Err(format!("forbidden access: {}", display))
+        }
+    }
+
+    /// The working directory as it was reached, through any links
+    pub(crate) fn cwd(&self) -> String {
+        self.cwd.to_string()
+    }
+
+    /// The working directory with every link resolved
+    pub(crate) fn physical_cwd(&self) -> String {
+        match self.resolve_dir(&self.cwd, "") {
+            Ok(dir) => self.full_path(dir.id),
+            Err(_) => self.cwd(),
+        }
+    }
+
+    pub(crate) fn cd(&mut self, dir: &str) -> Result<(), String> {
+        let path = self.path(dir);
+        self.check(self.resolve_dir(&path, dir)?, EXECUTE, dir)?;
+        self.cwd = path;
+        Ok(())
+    }
+
+    /// Parse `path` relative to the cwd
+    pub(crate) fn path(&self, path: &str) -> VirtualPath {
+        VirtualPath::parse(path, &self.cwd)
+    }
+
+    /// Walk from the root to the entry `path` names, following links on the way.
+    /// Every directory on the way must be searchable. The last entry is not
+    /// followed and is `None` if its directory exists but it doesn't. `display`
+    /// is the path as given, for the error messages.
+    fn walk(&self, path: &VirtualPath, display: &str) -> Result<Option<&FsEntry>, String> {
+        self.walk_links(path, display, &mut 0)
+    }
+
+    /// [`Self::walk`], following a link at the end too
+    fn walk_followed(&self, path: &VirtualPath, display: &str) -> Result<Option<&FsEntry>, String> {
+        let mut links = 0;
+        match self.walk_links(path, display, &mut links)? {
+            Some(entry) => self.follow_links(entry, &mut links).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// [`Self::walk`], counting the links followed in `links`, which is shared by
+    /// the whole resolution so that a path can't follow more than [`MAX_LINKS`]
+    fn walk_links(
+        &self,
+        path: &VirtualPath,
+        display: &str,
+        links: &mut usize,
+    ) -> Result<Option<&FsEntry>, String> {
+        let mut current = self.node(self.root);
+        let mut names = path.components().iter().peekable();
+        while let Some(name) = names.next() {
+            let dir = self.follow_links(current, links)?;
+            let FsEntryType::Directory(children) = &dir.ty else {
+                return Err(format!("not a directory: {}", display));
+            };
+            self.check(dir, EXECUTE, display)?;
+            match children.get(name) {
+                Some(&id) => current = self.node(id),
+                None if names.peek().is_none() => return Ok(None),
+                None => return Err(format!("no such directory: {}", display)),
             }
         }
-        None
+        Ok(Some(current))
     }
 
-    pub(crate) fn cwd(&self) -> &'static str {
-        match Self::find(&self.root, |node| node.id == self.cwd) {
-            Some(node) => node.full_path,
-            None => "",
+    /// Resolve `path` to a directory, following links. Its own permissions
+    /// are left to the caller.
+    fn resolve_dir(&self, path: &VirtualPath, display: &str) -> Result<&FsEntry, String> {
+        let dir = self
+            .walk_followed(path, display)?
+            .ok_or_else(|| format!("no such directory: {}", display))?;
+        match dir.ty {
+            FsEntryType::Directory(_) => Ok(dir),
+            _ => Err(format!("not a directory: {}", display)),
         }
     }
 
-    fn get_cwd(&self) -> &FsEntry {
-        Self::find(&self.root, |node| node.id == self.cwd)
-            .context("Searching for cwd")
-            .unwrap()
+    /// Resolve `path` to an existing regular file, following links. Its own
+    /// permissions are left to the caller.
+    fn resolve_file(&self, path: &VirtualPath, display: &str) -> Result<Option<&FsEntry>, String> {
+        let Some(dest) = self.walk_followed(path, display)? else {
+            return Ok(None);
+        };
+        match dest.ty {
+            FsEntryType::Directory(_) => Err(format!("is a directory: {}", display)),
+            _ if path.is_dir() => Err(format!("not a directory: {}", display)),
+            FsEntryType::File(_) | FsEntryType::Dynamic(_) => Ok(Some(dest)),
+            FsEntryType::Link(_) => {
+                unreachable!("After following links the node can only be a file or directory")
+            }
+        }
+    }
+
+    /// The entry at `path` itself, a link is not followed
+    pub(crate) fn entry(&self, path: &str) -> Result<LsResult, String> {
+        self.walk(&self.path(path), path)?
+            .map(LsResult::from)
+            .ok_or_else(|| format!("no such file or directory: {}", path))
+    }
+
+    /// The contents of the file at `path` whatever its permissions, for the
+    /// files the system reads itself like `/etc/passwd`
+    pub(crate) fn system_file(&self, path: &str) -> Option<&str> {
+        match self.resolve_file(&self.path(path), path).ok()?? {
+            FsEntry {
+                ty: FsEntryType::File(contents),
+                ..
+            } => Some(contents),
+            _ => None,
+        }
+    }
+
+    /// What the link at `path` points to, as it was given
+    pub(crate) fn read_link(&self, path: &str) -> Result<&str, String> {
+        match self.walk(&self.path(path), path)? {
+            Some(FsEntry {
+                ty: FsEntryType::Link(target),
+                ..
+            }) => Ok(target),
+            Some(_) => Err(format!("not a link: {}", path)),
+            None => Err(format!("no such file or directory: {}", path)),
+        }
+    }
+
+    /// The absolute path of `path` with every link resolved. Only the last
+    /// component may be missing.
+    pub(crate) fn canonicalize(&self, path: &str) -> Result<String, String> {
+        let target = self.path(path);
+        if let Some(dest) = self.walk_followed(&target, path)? {
+            return Ok(self.full_path(dest.id));
+        }
+        let (Some(parent), Some(name)) = (target.parent(), target.file_name()) else {
+            return Err(format!("no such file or directory: {}", path));
+        };
+        let dir = self.full_path(self.resolve_dir(&parent, path)?.id);
+        Ok(format!("{}/{}", dir.trim_end_matches('/'), name))
+    }
+
+    /// Whether there is an entry at `path`, without following a link at the end
+    pub(crate) fn exists(&self, path: &str) -> bool {
+        matches!(self.walk(&self.path(path), path), Ok(Some(_)))
+    }
+
+    /// An id that is the same for every path to the directory, through any links
+    pub(crate) fn dir_id(&self, path: &str) -> Result<u64, String> {
+        self.resolve_dir(&self.path(path), path).map(|dir| dir.id)
+    }
+
+    pub(crate) fn is_dir(&self, path: &str) -> bool {
+        self.resolve_dir(&self.path(path), path).is_ok()
     }
 
-    fn get_parent(&self, node: &FsEntry) -> Option<&FsEntry> {
-        Self::find(&self.root, |n: &FsEntry| {
-            if let FsEntryType::Directory(children) = &n.ty {
-                for child in children {
-                    if child.id == node.id {
-                        return true;
+    pub(crate) fn read_file(&self, path: &str) -> Result<Cow<'_, str>, String> {
+        match self.resolve_file(&self.path(path), path)? {
+            Some(
+                file @ FsEntry {
+                    ty: FsEntryType::File(contents),
+                    ..
+                },
+            ) => {
+                self.check(file, READ, path)?;
+                Ok(Cow::Borrowed(contents))
+            }
+            Some(
+                file @ FsEntry {
+                    ty: FsEntryType::Dynamic(kind),
+                    ..
+                },
+            ) => {
+                self.check(file, READ, path)?;
+                Ok(Cow::Owned(kind.contents()))
+            }
+            _ => Err(format!("no such file: {}", path)),
+        }
+    }
+
+    /// Write `contents` to the file at `path`, creating it if it doesn't exist
+    pub(crate) fn write_file(
+        &mut self,
+        path: &str,
+        contents: &str,
+        append: bool,
+    ) -> Result<(), String> {
+        let display = path;
+        let path = self.path(display);
+        let target = match self.resolve_file(&path, display)? {
+            Some(file) => {
+                self.check(file, WRITE, display)?;
+                // Devices throw away what is written, the other dynamic files can't change
+                match file.ty {
+                    FsEntryType::Dynamic(kind) if kind.is_device() => return Ok(()),
+                    FsEntryType::Dynamic(_) => {
+                        return Err(format!("operation not permitted: {}", display))
                     }
+                    _ => file.id,
                 }
             }
-            return false;
-        })
+            None => {
+                let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+                    return Err(format!("is a directory: {}", display));
+                };
+                if path.is_dir() {
+                    return Err(format!("not a directory: {}", display));
+                }
+                let dir = self.resolve_dir(&parent, display)?;
+                self.check(dir, WRITE | EXECUTE, display)?;
+                let dir = dir.id;
+                self.insert(dir, name, FsEntryType::File(String::new()))
+            }
+        };
+
+        let file = self.node_mut(target);
+        file.modified = now();
+        if let FsEntryType::File(existing) = &mut file.ty {
+            if !append {
+                existing.clear();
+            }
+            existing.push_str(contents);
+            file.mime = mime_type(&file.name, existing);
+        }
+        self.record(target);
+        Ok(())
     }
 
-    pub(crate) fn cd(&mut self, dir: String) -> Result<(), String> {
-        let splits = dir.split("/");
-        for dir_name in splits {
-            let mut new_current_dir: u64 = 0;
-            let cwd = self.get_cwd();
-            match dir_name {
-                "." => {
-                    continue;
-                }
-                ".." => {
-                    if let Some(new_dir) = self.get_parent(cwd) {
-                        new_current_dir = new_dir.id;
-                    } else {
-                        return Err(format!("no such directory: {}", dir));
+    /// The changes made by the user, to be saved
+    pub(crate) fn overlay(&self) -> &Overlay {
+        &self.overlay
+    }
+
+    /// Drop every change made by the user
+    pub(crate) fn reset(&mut self) {
+        let cwd = self.cwd.clone();
+        let (user, groups) = (self.user.clone(), self.groups.clone());
+        *self = Self::builtin();
+        self.login(&user, groups);
+        if self.resolve_dir(&cwd, "").is_ok() {
+            self.cwd = cwd;
+        }
+    }
+
+    /// Recreate the changes of `overlay` on top of the built-in content
+    fn apply(&mut self, overlay: Overlay) {
+        // The changes are made whatever the permissions, as the user who made them
+        let user = std::mem::replace(&mut self.user, ROOT.to_string());
+        for path in overlay.removed() {
+            if let Ok(Some(entry)) = self.walk(&self.path(path), path) {
+                self.remove_node(entry.id);
+            }
+        }
+        for (path, entry) in overlay.entries() {
+            let path_buf = self.path(path);
+            let (Some(parent), Some(name)) = (path_buf.parent(), path_buf.file_name()) else {
+                continue;
+            };
+            let Ok(parent) = self.resolve_dir(&parent, path).map(|dir| dir.id) else {
+                continue;
+            };
+            if let Some(existing) = self.child(parent, name) {
+                let node = self.node_mut(existing);
+                match (&mut node.ty, entry) {
+                    (FsEntryType::Directory(_), OverlayEntry::Directory) => continue,
+                    (FsEntryType::File(contents), OverlayEntry::File(new)) => {
+                        contents.clone_from(new);
+                        node.mime = mime_type(&node.name, new);
+                        continue;
                     }
+                    _ => self.remove_node(existing),
                 }
-                dir_name => match &cwd.ty {
-                    FsEntryType::Directory(children) => {
-                        let mut has_matched = false;
-                        for entry in children {
-                            match entry.ty {
-                                FsEntryType::Directory(_) => {
-                                    if entry.name == dir_name {
-                                        if !entry.accessible {
-                                            return Err(format!("forbidden access: {}", dir));
-                                        }
-                                        new_current_dir = entry.id;
-                                        has_matched = true;
-                                    }
-                                }
-                                FsEntryType::File(_) => {
-                                    return Err(format!("not a directory: {}", dir))
-                                }
-                                FsEntryType::Link(_) => {
-                                    if entry.name == dir_name {
-                                        if !entry.accessible {
-                                            return Err(format!("forbidden access: {}", dir));
-                                        }
-                                        match self.follow_link(&entry) {
-                                                Some(link_dest) => match link_dest.ty {
-                                                    FsEntryType::Directory(_) => {
-                                                        if !link_dest.accessible {
-                                                            return Err(format!(
-                                                                "forbidden access: {}",
-                                                                dir
-                                                            ));
-                                                        }
-                                                        new_current_dir = link_dest.id;
-                                                        has_matched = true;
-                                                    }
-                                                    FsEntryType::File(_) => {
-                                                        return Err(format!("not a directory: {}", dir))
-                                                    }
-                                                    _ => unreachable!("After follow_link the node can only be a file or directory"),
-                                                },
-                                                None => return Err("broken link".to_string()),
-                                            }
-                                    }
-                                }
-                            }
-                        }
-                        if !has_matched {
-                            return Err(format!("no such directory: {}", dir));
-                        }
-                    }
-                    _ => unreachable!(),
-                },
             }
-            self.cwd = new_current_dir;
+            let id = match entry {
+                OverlayEntry::Directory => self.add_dir(parent, name),
+                OverlayEntry::File(contents) => {
+                    self.insert(parent, name, FsEntryType::File(contents.clone()))
+                }
+                OverlayEntry::Link(target) => {
+                    self.insert(parent, name, FsEntryType::Link(target.clone()))
+                }
+                OverlayEntry::Dynamic(kind) => {
+                    self.insert(parent, name, FsEntryType::Dynamic(*kind))
+                }
+            };
+            // Until its attributes are restored below, the node is the user's
+            self.node_mut(id).owner.clone_from(&user);
+        }
+        for (path, attributes) in overlay.attributes() {
+            if let Ok(Some(entry)) = self.walk(&self.path(path), path) {
+                let node = self.node_mut(entry.id);
+                node.mode = attributes.mode;
+                node.owner.clone_from(&attributes.owner);
+                node.group.clone_from(&attributes.group);
+                node.modified = attributes.modified;
+            }
         }
+        self.user = user;
+        self.overlay = overlay;
+    }
 
+    /// The absolute path of a node, following the parent links
+    fn full_path(&self, id: u64) -> String {
+        let mut names = vec![];
+        let mut current = self.node(id);
+        while let Some(parent) = current.parent {
+            names.push(current.name.as_str());
+            current = self.node(parent);
+        }
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    /// The id of the entry `name` in the directory `dir`
+    fn child(&self, dir: u64, name: &str) -> Option<u64> {
+        match &self.node(dir).ty {
+            FsEntryType::Directory(children) => children.get(name).copied(),
+            _ => None,
+        }
+    }
+
+    /// Whether `id` is `ancestor` or somewhere below it
+    fn is_within(&self, id: u64, ancestor: u64) -> bool {
+        let mut current = Some(id);
+        while let Some(id) = current {
+            if id == ancestor {
+                return true;
+            }
+            current = self.node(id).parent;
+        }
+        false
+    }
+
+    /// The ids of a node and everything below it
+    fn subtree(&self, id: u64) -> Vec<u64> {
+        let mut ids = vec![id];
+        let mut idx = 0;
+        while let Some(&id) = ids.get(idx) {
+            if let FsEntryType::Directory(children) = &self.node(id).ty {
+                ids.extend(children.values());
+            }
+            idx += 1;
+        }
+        ids
+    }
+
+    /// Whether changes to a node are saved, which they aren't in `/tmp`
+    fn persistent(&self, id: u64) -> bool {
+        !system::is_temporary(&self.full_path(id))
+    }
+
+    /// Remember a node and everything below it in the overlay
+    fn record(&mut self, id: u64) {
+        if !self.persistent(id) {
+            return;
+        }
+        for id in self.subtree(id) {
+            let entry = match &self.node(id).ty {
+                FsEntryType::Directory(_) => OverlayEntry::Directory,
+                FsEntryType::File(contents) => OverlayEntry::File(contents.clone()),
+                FsEntryType::Link(target) => OverlayEntry::Link(target.clone()),
+                FsEntryType::Dynamic(kind) => OverlayEntry::Dynamic(*kind),
+            };
+            let path = self.full_path(id);
+            self.overlay.set(path, entry);
+            self.record_attributes(id);
+        }
+    }
+
+    /// Remember the mode, owner, group and modification time of a node in the overlay
+    fn record_attributes(&mut self, id: u64) {
+        if !self.persistent(id) {
+            return;
+        }
+        let node = self.node(id);
+        let attributes = Attributes {
+            mode: node.mode,
+            owner: node.owner.clone(),
+            group: node.group.clone(),
+            modified: node.modified,
+        };
+        let path = self.full_path(id);
+        self.overlay.set_attributes(path, attributes);
+    }
+
+    fn record_removal(&mut self, id: u64) {
+        if !self.persistent(id) {
+            return;
+        }
+        let path = self.full_path(id);
+        let builtin = self.node(id).builtin;
+        self.overlay.remove(&path, builtin);
+    }
+
+    fn detach(&mut self, id: u64) {
+        let node = self.node(id);
+        let (Some(parent), name) = (node.parent, node.name.clone()) else {
+            return;
+        };
+        if let FsEntryType::Directory(children) = &mut self.node_mut(parent).ty {
+            children.remove(&name);
+        }
+    }
+
+    fn remove_node(&mut self, id: u64) {
+        self.detach(id);
+        for id in self.subtree(id) {
+            self.nodes.remove(&id);
+        }
+    }
+
+    /// Where `src_name` ends up when moved or copied to `to`: inside `to` if it
+    /// is a directory, otherwise `to` itself. Returns the directory, which the
+    /// user may write to, and the name.
+    fn destination(&self, to: &str, src_name: &str) -> Result<(u64, String), String> {
+        let path = self.path(to);
+        let (dir, name) = match self.resolve_dir(&path, to) {
+            Ok(dir) => (dir, src_name),
+            Err(_) => {
+                let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+                    return Err(format!("no such directory: {}", to));
+                };
+                if path.is_dir() {
+                    return Err(format!("no such directory: {}", to));
+                }
+                (self.resolve_dir(&parent, to)?, name)
+            }
+        };
+        self.check(dir, WRITE | EXECUTE, to)?;
+        Ok((dir.id, name.to_string()))
+    }
+
+    /// Make room for `src` at `name` in `dir`, removing a file that is in the way
+    fn replace(&mut self, src: u64, dir: u64, name: &str, to: &str) -> Result<(), String> {
+        let Some(existing) = self.child(dir, name) else {
+            return Ok(());
+        };
+        match (&self.node(existing).ty, &self.node(src).ty) {
+            (FsEntryType::Directory(_), _) => Err(format!("cannot overwrite directory: {}", to)),
+            (_, FsEntryType::Directory(_)) => {
+                Err(format!("cannot overwrite non-directory: {}", to))
+            }
+            _ => {
+                self.record_removal(existing);
+                self.remove_node(existing);
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn mkdir(&mut self, path: &str, parents: bool) -> Result<(), String> {
+        let target = self.path(path);
+        if !parents {
+            let (Some(parent), Some(name)) = (target.parent(), target.file_name()) else {
+                return Err(format!("file exists: {}", path));
+            };
+            let dir = self.resolve_dir(&parent, path)?;
+            if self.child(dir.id, name).is_some() {
+                return Err(format!("file exists: {}", path));
+            }
+            self.check(dir, WRITE | EXECUTE, path)?;
+            let id = self.add_dir(dir.id, name);
+            self.record(id);
+            return Ok(());
+        }
+
+        let mut current = VirtualPath::root();
+        for name in target.components() {
+            // Fails if a file is in the way
+            let dir = self.resolve_dir(&current, path)?;
+            current = current.join(name);
+            if self.child(dir.id, name).is_none() {
+                self.check(dir, WRITE | EXECUTE, path)?;
+                let id = self.add_dir(dir.id, name);
+                self.record(id);
+            }
+        }
+        self.resolve_dir(&target, path).map(|_| ())
+    }
+
+    pub(crate) fn remove(&mut self, path: &str, recursive: bool) -> Result<(), String> {
+        if matches!(
+            path.trim_end_matches('/').rsplit('/').next(),
+            Some("." | "..")
+        ) {
+            return Err(format!("refusing to remove '.' or '..': {}", path));
+        }
+        let target = self.path(path);
+        if target.components().is_empty() {
+            return Err(format!("refusing to remove the root directory: {}", path));
+        }
+        let entry = self
+            .walk(&target, path)?
+            .ok_or_else(|| format!("no such file or directory: {}", path))?;
+        match entry.ty {
+            FsEntryType::Directory(_) if !recursive => {
+                return Err(format!("is a directory: {}", path))
+            }
+            FsEntryType::File(_) | FsEntryType::Link(_) | FsEntryType::Dynamic(_)
+                if target.is_dir() =>
+            {
+                return Err(format!("not a directory: {}", path))
+            }
+            _ => {}
+        }
+        if let Some(parent) = entry.parent {
+            self.check(self.node(parent), WRITE | EXECUTE, path)?;
+        }
+        // The contents of a directory are removed from it first
+        for id in self.subtree(entry.id) {
+            let node = self.node(id);
+            if matches!(&node.ty, FsEntryType::Directory(children) if !children.is_empty()) {
+                self.check(node, READ | WRITE | EXECUTE, path)?;
+            }
+        }
+        let id = entry.id;
+        self.record_removal(id);
+        self.remove_node(id);
+        Ok(())
+    }
+
+    /// Create a link to `target` at `path`, or inside it if it is a directory.
+    /// The target is stored as given, it doesn't have to exist. With `force` a
+    /// file in the way is replaced.
+    pub(crate) fn symlink(&mut self, target: &str, path: &str, force: bool) -> Result<(), String> {
+        let target_name = target
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or("");
+        let (dir, name) = self.destination(path, target_name)?;
+        if name.is_empty() {
+            return Err(format!("file exists: {}", path));
+        }
+        if let Some(existing) = self.child(dir, &name) {
+            let node = self.node(existing);
+            if !force {
+                return Err(format!("file exists: {}", path));
+            }
+            if let FsEntryType::Directory(_) = node.ty {
+                return Err(format!("cannot overwrite directory: {}", path));
+            }
+            self.record_removal(existing);
+            self.remove_node(existing);
+        }
+        let id = self.insert(dir, &name, FsEntryType::Link(target.to_string()));
+        self.record(id);
         Ok(())
     }
 
-    pub(crate) fn ls(&self) -> Result<Vec<LsResult>, String> {
+    /// Move or rename `from` to `to`
+    pub(crate) fn rename(&mut self, from: &str, to: &str) -> Result<(), String> {
+        let src_path = self.path(from);
+        if src_path.components().is_empty() {
+            return Err(format!("cannot move the root directory: {}", from));
+        }
+        let src = self
+            .walk(&src_path, from)?
+            .ok_or_else(|| format!("no such file or directory: {}", from))?;
+        if let Some(parent) = src.parent {
+            self.check(self.node(parent), WRITE | EXECUTE, from)?;
+        }
+        let (src, src_name) = (src.id, src.name.clone());
+        let (dir, name) = self.destination(to, &src_name)?;
+        if self.child(dir, &name) == Some(src) {
+            return Ok(());
+        }
+        if self.is_within(dir, src) {
+            return Err(format!("cannot move a directory into itself: {}", to));
+        }
+        self.replace(src, dir, &name, to)?;
+
+        self.record_removal(src);
+        self.detach(src);
+        let node = self.node_mut(src);
+        node.name.clone_from(&name);
+        node.parent = Some(dir);
+        if let FsEntryType::Directory(children) = &mut self.node_mut(dir).ty {
+            children.insert(name, src);
+        }
+        for id in self.subtree(src) {
+            self.node_mut(id).builtin = false;
+        }
+        self.record(src);
+        Ok(())
+    }
+
+    /// Copy `from` to `to`. Directories are only copied with `recursive`.
+    pub(crate) fn copy(&mut self, from: &str, to: &str, recursive: bool) -> Result<(), String> {
+        let src_path = self.path(from);
+        let mut links = 0;
+        let entry = self
+            .walk_links(&src_path, from, &mut links)?
+            .ok_or_else(|| format!("no such file or directory: {}", from))?;
+        let src = self.follow_links(entry, &mut links)?;
+        if let FsEntryType::Directory(_) = src.ty {
+            if !recursive {
+                return Err(format!("omitting directory: {}", from));
+            }
+        }
+        for id in self.subtree(src.id) {
+            let node = self.node(id);
+            match node.ty {
+                FsEntryType::Directory(_) => self.check(node, READ | EXECUTE, from)?,
+                FsEntryType::File(_) | FsEntryType::Dynamic(_) => self.check(node, READ, from)?,
+                FsEntryType::Link(_) => {}
+            }
+        }
+        let (src, src_name) = (src.id, entry.name.clone());
+        let (dir, name) = self.destination(to, &src_name)?;
+        if self.is_within(dir, src) {
+            return Err(format!("cannot copy a directory into itself: {}", to));
+        }
+        if self.child(dir, &name) == Some(src) {
+            return Err(format!("same file: {}", to));
+        }
+        self.replace(src, dir, &name, to)?;
+
+        let id = self.copy_node(src, dir, &name);
+        self.record(id);
+        Ok(())
+    }
+
+    /// Copy `src` to `name` in `dir`, keeping its mode. The copy belongs to the
+    /// user, and a dynamic file is copied as a file with its current contents.
+    fn copy_node(&mut self, src: u64, dir: u64, name: &str) -> u64 {
+        let node = self.node(src);
+        let mode = node.mode;
+        let id = match node.ty.clone() {
+            FsEntryType::Directory(children) => {
+                let id = self.insert(dir, name, FsEntryType::Directory(BTreeMap::new()));
+                for (child_name, child) in children {
+                    self.copy_node(child, id, &child_name);
+                }
+                id
+            }
+            FsEntryType::Dynamic(kind) => {
+                self.insert(dir, name, FsEntryType::File(kind.contents()))
+            }
+            ty => self.insert(dir, name, ty),
+        };
+        self.node_mut(id).mode = mode;
+        id
+    }
+
+    /// The ids of `path` and everything below it with `recursive`, following a link
+    /// at `path` but not below it
+    fn targets(&self, path: &str, recursive: bool) -> Result<Vec<u64>, String> {
+        let target = self
+            .walk_followed(&self.path(path), path)?
+            .ok_or_else(|| format!("no such file or directory: {}", path))?
+            .id;
+        if !recursive {
+            return Ok(vec![target]);
+        }
+        Ok(self
+            .subtree(target)
+            .into_iter()
+            .filter(|&id| !matches!(self.node(id).ty, FsEntryType::Link(_)))
+            .collect())
+    }
+
+    /// Set the mode of `path`, and everything below it with `recursive`, to what
+    /// `change` makes of the current mode and whether the node is a directory.
+    /// Only the owner of a node and root may change its mode.
+    pub(crate) fn chmod(
+        &mut self,
+        path: &str,
+        recursive: bool,
+        change: impl Fn(u32, bool) -> u32,
+    ) -> Result<(), String> {
+        let mut errors = vec![];
+        for id in self.targets(path, recursive)? {
+            let node = self.node(id);
+            if self.user != ROOT && node.owner != self.user {
+                errors.push(format!("operation not permitted: {}", self.full_path(id)));
+                continue;
+            }
+            let mode = change(node.mode, matches!(node.ty, FsEntryType::Directory(_)));
+            self.node_mut(id).mode = mode & 0o777;
+            self.record_attributes(id);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    /// Change the owner and the group of `path`, and everything below it with
+    /// `recursive`. Only root may give a node away; its owner may only change
+    /// its group to one of theirs.
+    pub(crate) fn chown(
+        &mut self,
+        path: &str,
+        recursive: bool,
+        owner: Option<&str>,
+        group: Option<&str>,
+    ) -> Result<(), String> {
+        let mut errors = vec![];
+        for id in self.targets(path, recursive)? {
+            let node = self.node(id);
+            let permitted = self.user == ROOT
+                || (node.owner == self.user
+                    && owner.is_none_or(|owner| owner == self.user)
+                    && group.is_none_or(|group| self.groups.iter().any(|own| own == group)));
+            if !permitted {
+                errors.push(format!("operation not permitted: {}", self.full_path(id)));
+                continue;
+            }
+            let node = self.node_mut(id);
+            if let Some(owner) = owner {
+                node.owner = owner.to_string();
+            }
+            if let Some(group) = group {
+                node.group = group.to_string();
+            }
+            self.record_attributes(id);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    /// List the entries of the directory `dir`, relative to the cwd
+    pub(crate) fn list(&self, dir: &str) -> Result<Vec<LsResult>, String> {
         let mut results = Vec::<LsResult>::new();
-        let cwd = self.get_cwd();
-        match &cwd.ty {
+        let entry = self.resolve_dir(&self.path(dir), dir)?;
+        self.check(entry, READ, dir)?;
+        match &entry.ty {
             FsEntryType::Directory(contents) => {
-                for entry in contents {
-                    results.push(LsResult::from(entry))
+                for &id in contents.values() {
+                    results.push(LsResult::from(self.node(id)))
                 }
             }
             _ => unreachable!(),
@@ -220,34 +1022,52 @@ impl Filesystem {
         Ok(results)
     }
 
-    fn follow_link<'a>(&'a self, link: &'a FsEntry) -> Option<&'a FsEntry> {
-        if let FsEntryType::Link(to) = link.ty {
-            let to = Self::find(&self.root, |node| node.id == to)?;
-            if let FsEntryType::Link(_) = to.ty {
-                self.follow_link(to)
-            } else {
-                Some(to)
+    /// The file or directory at the end of a chain of links, counting the links
+    /// followed in `links`. Fails if the chain is broken or too long.
+    fn follow_links<'a>(
+        &'a self,
+        mut entry: &'a FsEntry,
+        links: &mut usize,
+    ) -> Result<&'a FsEntry, String> {
+        while let FsEntryType::Link(target) = &entry.ty {
+            if *links >= MAX_LINKS {
+                return Err(TOO_MANY_LINKS.to_string());
             }
-        } else {
-            Some(link)
+            *links += 1;
+            let parent = entry.parent.ok_or("broken link")?;
+            // A relative target is relative to the directory of the link
+            let dir = VirtualPath::parse(&self.full_path(parent), &VirtualPath::root());
+            let target = VirtualPath::parse(target, &dir);
+            entry = match self.walk_links(&target, "", links) {
+                Ok(Some(dest)) => dest,
+                Err(err) if err == TOO_MANY_LINKS => return Err(err),
+                _ => return Err("broken link".to_string()),
+            };
         }
+        Ok(entry)
     }
 }
 
-fn map_accessible(is_accessible: bool) -> String {
-    if is_accessible {
-        "rwx".to_string()
-    } else {
-        "---".to_string()
-    }
-}
-
-fn type_flag(ty: &FsEntryType) -> &'static str {
-    match ty {
-        FsEntryType::File(_) => ".",
+/// The mode as `ls -l` prints it, like `drwxr-xr-x`
+fn mode_string(ty: &FsEntryType, mode: u32) -> String {
+    let mut string = match ty {
+        FsEntryType::File(_) => "-",
         FsEntryType::Directory(_) => "d",
         FsEntryType::Link(_) => "l",
+        FsEntryType::Dynamic(kind) if kind.is_device() => "c",
+        FsEntryType::Dynamic(_) => "-",
     }
+    .to_string();
+    for shift in [6, 3, 0] {
+        for (bit, flag) in [(READ, 'r'), (WRITE, 'w'), (EXECUTE, 'x')] {
+            string.push(if (mode >> shift) & bit != 0 {
+                flag
+            } else {
+                '-'
+            });
+        }
+    }
+    string
 }
 
 pub(crate) enum LsResultType {
@@ -260,7 +1080,7 @@ impl From<&FsEntryType> for LsResultType {
     fn from(value: &FsEntryType) -> Self {
         match value {
             FsEntryType::Directory(_) => Self::Directory,
-            FsEntryType::File(_) => Self::File,
+            FsEntryType::File(_) | FsEntryType::Dynamic(_) => Self::File,
             FsEntryType::Link(_) => Self::Link,
         }
     }
@@ -269,17 +1089,22 @@ impl From<&FsEntryType> for LsResultType {
 impl From<&FsEntry> for LsResult {
     fn from(value: &FsEntry) -> Self {
         let mut res = Self {
-            name: value.name,
-            permissions: format!(
-                "{}{}",
-                type_flag(&value.ty),
-                map_accessible(value.accessible)
-            ),
+            name: value.name.clone(),
+            permissions: mode_string(&value.ty, value.mode),
+            mode: value.mode,
             size: None,
+            modified: value.modified,
+            owner: value.owner.clone(),
+            group: value.group.clone(),
+            mime: value.mime,
             ty: LsResultType::from(&value.ty),
+            target: None,
         };
-        if let FsEntryType::File(contents) = &value.ty {
-            res.size = Some(contents.len() as u64)
+        match &value.ty {
+            FsEntryType::File(contents) => res.size = Some(contents.len() as u64),
+            FsEntryType::Link(target) => res.target = Some(target.clone()),
+            FsEntryType::Dynamic(kind) => res.size = Some(kind.size()),
+            FsEntryType::Directory(_) => {}
         }
         res
     }
@@ -287,9 +1112,16 @@ impl From<&FsEntry> for LsResult {
 
 pub(crate) struct LsResult {
     permissions: String,
-    name: &'static str,
+    mode: u32,
+    name: String,
     size: Option<u64>,
+    modified: u64,
+    owner: String,
+    group: String,
+    mime: &'static str,
     ty: LsResultType,
+    /// What a link points to
+    target: Option<String>,
 }
 
 impl LsResult {
@@ -297,8 +1129,20 @@ impl LsResult {
         self.size
     }
 
-    pub(crate) fn name(&self) -> &'static str {
-        self.name
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The same entry, shown as `name`
+    pub(crate) fn renamed(self, name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..self
+        }
+    }
+
+    pub(crate) fn modified(&self) -> u64 {
+        self.modified
     }
 
     pub(crate) fn ty(&self) -> &LsResultType {
@@ -308,4 +1152,188 @@ impl LsResult {
     pub(crate) fn permissions(&self) -> &str {
         &self.permissions
     }
+
+    pub(crate) fn mode(&self) -> u32 {
+        self.mode
+    }
+
+    pub(crate) fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    pub(crate) fn group(&self) -> &str {
+        &self.group
+    }
+
+    pub(crate) fn mime(&self) -> &str {
+        self.mime
+    }
+
+    pub(crate) fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The filesystem saved as `overlay` after a reload
+    fn load(overlay: Overlay) -> Filesystem {
+        Filesystem::from(&LocalStorageSettings {
+            filesystem: overlay,
+            ..Default::default()
+        })
+    }
+
+    /// The id of the node at the absolute `path`, found through the child indexes
+    fn id(fs: &Filesystem, path: &str) -> u64 {
+        fs.walk(&fs.path(path), path).unwrap().unwrap().id
+    }
+
+    #[test]
+    fn nodes_link_to_their_parents_and_children() {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.mkdir("/tmp/a/b/c", true).unwrap();
+        let (a, b, c) = (
+            id(&fs, "/tmp/a"),
+            id(&fs, "/tmp/a/b"),
+            id(&fs, "/tmp/a/b/c"),
+        );
+        assert_eq!(fs.child(a, "b"), Some(b));
+        assert_eq!(fs.child(b, "c"), Some(c));
+        assert_eq!(fs.child(b, "x"), None);
+        assert_eq!(fs.node(c).parent, Some(b));
+        assert_eq!(fs.node(b).parent, Some(a));
+        assert_eq!(fs.node(fs.root).parent, None);
+        assert_eq!(fs.full_path(c), "/tmp/a/b/c");
+        assert!(fs.is_within(c, a));
+        assert!(!fs.is_within(a, c));
+        assert_eq!(fs.subtree(a), [a, b, c]);
+    }
+
+    #[test]
+    fn moves_and_removals_keep_the_index_consistent() {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.mkdir("/tmp/a/b", true).unwrap();
+        fs.mkdir("/tmp/z", false).unwrap();
+        let b = id(&fs, "/tmp/a/b");
+        fs.rename("/tmp/a/b", "/tmp/z/renamed").unwrap();
+        // The node keeps its id, only its name and parent change
+        assert_eq!(id(&fs, "/tmp/z/renamed"), b);
+        assert_eq!(fs.node(b).parent, Some(id(&fs, "/tmp/z")));
+        assert_eq!(fs.child(id(&fs, "/tmp/a"), "b"), None);
+        assert_eq!(fs.full_path(b), "/tmp/z/renamed");
+
+        let nodes = fs.nodes.len();
+        fs.remove("/tmp/z", true).unwrap();
+        assert_eq!(fs.nodes.len(), nodes - 2);
+        assert!(!fs.nodes.contains_key(&b));
+        assert_eq!(fs.child(id(&fs, "/tmp"), "z"), None);
+    }
+
+    #[test]
+    fn changes_survive_a_reload() {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.mkdir("/home/guest/d/e", true).unwrap();
+        fs.write_file("/home/guest/d/e/f.md", "# f", false).unwrap();
+        fs.write_file("/home/guest/welcome.md", "changed", false)
+            .unwrap();
+        fs.symlink("d/e", "/home/guest/link", false).unwrap();
+        fs.chmod("/home/guest/d", false, |_, _| 0o700).unwrap();
+        fs.remove("/home/guest/projects", true).unwrap();
+        fs.write_file("/tmp/lost", "", false).unwrap();
+
+        let fs = load(fs.overlay().clone());
+        assert_eq!(fs.read_file("/home/guest/d/e/f.md").unwrap(), "# f");
+        assert_eq!(fs.read_file("/home/guest/welcome.md").unwrap(), "changed");
+        assert_eq!(fs.read_link("/home/guest/link").unwrap(), "d/e");
+        assert_eq!(fs.entry("/home/guest/d").unwrap().mode(), 0o700);
+        assert!(!fs.exists("/home/guest/projects"));
+        assert!(!fs.exists("/tmp/lost"));
+    }
+
+    #[test]
+    fn owners_groups_and_times_survive_a_reload() {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.write_file("/home/guest/f", "", false).unwrap();
+        let mut overlay = fs.overlay().clone();
+        let mut attributes = overlay.attributes()["/home/guest/f"].clone();
+        assert_eq!(attributes.owner, USER);
+        attributes.group = "staff".to_string();
+        attributes.modified = 1_000_000;
+        overlay.set_attributes("/home/guest/f".to_string(), attributes);
+
+        let fs = load(overlay);
+        let entry = fs.entry("/home/guest/f").unwrap();
+        assert_eq!(entry.owner(), USER);
+        assert_eq!(entry.group(), "staff");
+        assert_eq!(entry.modified(), 1_000_000);
+    }
+
+    #[test]
+    fn links_resolve_relative_to_their_directory() {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.mkdir("/tmp/l/dir", true).unwrap();
+        fs.write_file("/tmp/l/dir/f", "contents", false).unwrap();
+        fs.symlink("dir", "/tmp/l/rel", false).unwrap();
+        fs.symlink("/tmp/l/dir/f", "/tmp/l/abs", false).unwrap();
+        fs.symlink("rel/f", "/tmp/l/chain", false).unwrap();
+        fs.symlink("missing", "/tmp/l/broken", false).unwrap();
+
+        assert_eq!(fs.read_file("/tmp/l/rel/f").unwrap(), "contents");
+        assert_eq!(fs.read_file("/tmp/l/abs").unwrap(), "contents");
+        assert_eq!(fs.read_file("/tmp/l/chain").unwrap(), "contents");
+        assert!(fs.read_file("/tmp/l/broken").is_err());
+        assert!(fs.is_dir("/tmp/l/rel"));
+        assert_eq!(fs.read_link("/tmp/l/chain").unwrap(), "rel/f");
+        assert_eq!(fs.canonicalize("/tmp/l/chain").unwrap(), "/tmp/l/dir/f");
+        assert_eq!(fs.entry("/tmp/l/rel").unwrap().target(), Some("dir"));
+
+        fs.cd("/tmp/l/rel").unwrap();
+        assert_eq!(fs.cwd(), "/tmp/l/rel");
+        assert_eq!(fs.physical_cwd(), "/tmp/l/dir");
+    }
+
+    #[test]
+    fn at_most_max_links_are_followed() {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.mkdir("/tmp/l", false).unwrap();
+        fs.write_file("/tmp/l/0", "end", false).unwrap();
+        for idx in 1..=MAX_LINKS + 1 {
+            fs.symlink(&(idx - 1).to_string(), &format!("/tmp/l/{}", idx), false)
+                .unwrap();
+        }
+        assert_eq!(
+            fs.read_file(&format!("/tmp/l/{}", MAX_LINKS)).unwrap(),
+            "end"
+        );
+        assert_eq!(
+            fs.read_file(&format!("/tmp/l/{}", MAX_LINKS + 1)),
+            Err(TOO_MANY_LINKS.to_string())
+        );
+
+        fs.symlink("loop", "/tmp/l/loop", false).unwrap();
+        assert_eq!(fs.read_file("/tmp/l/loop"), Err(TOO_MANY_LINKS.to_string()));
+    }
+
+    #[test]
+    fn links_are_counted_over_the_whole_path() {
+        // Every link doubles the links its target goes through, so counting
+        // per link would take exponential time
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.mkdir("/tmp/l", false).unwrap();
+        fs.symlink(".", "/tmp/l/l0", false).unwrap();
+        for idx in 1..=20 {
+            let prev = format!("l{}", idx - 1);
+            fs.symlink(
+                &format!("{}/{}", prev, prev),
+                &format!("/tmp/l/l{}", idx),
+                false,
+            )
+            .unwrap();
+        }
+        assert_eq!(fs.dir_id("/tmp/l/l20"), Err(TOO_MANY_LINKS.to_string()));
+        assert!(fs.is_dir("/tmp/l/l3"));
+    }
 }
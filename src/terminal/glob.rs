@@ -0,0 +1,346 @@
+use super::{
+    expand::Field,
+    filesystem::{Filesystem, LsResultType},
+};
+
+/// Whether `name` matches the shell pattern `pattern`. `*` matches any text,
+/// `?` any character and `[abc]`, `[a-z]` or `[!abc]` one character of a set.
+/// A backslash makes the next character literal.
+pub(crate) fn matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    matches_from(&pattern, &name)
+}
+
+/// Match with the usual greedy loop: each `*` first matches nothing, and when
+/// the rest fails, the last `*` takes one more character. Earlier stars never
+/// need to be revisited, so this is linear in the pattern times the name.
+fn matches_from(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // The pattern index after the last `*`, and the name index it matched up to
+    let mut backtrack = None;
+    while n < name.len() {
+        if pattern.get(p) == Some(&'*') {
+            p += 1;
+            backtrack = Some((p, n));
+        } else if let Some(len) = match_one(&pattern[p..], name[n]) {
+            p += len;
+            n += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            p = star_p;
+            n = star_n + 1;
+            backtrack = Some((star_p, n));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&chr| chr == '*')
+}
+
+/// The length of the element at the start of `pattern`, which is not a `*`,
+/// if it matches `chr`
+fn match_one(pattern: &[char], chr: char) -> Option<usize> {
+    match pattern.first()? {
+        '?' => Some(1),
+        '[' => match class(pattern) {
+            Some((matcher, len)) => matcher(chr).then_some(len),
+            // An unterminated class is a literal `[`
+            None => (chr == '[').then_some(1),
+        },
+        '\\' if pattern.len() > 1 => (chr == pattern[1]).then_some(2),
+        first => (*first == chr).then_some(1),
+    }
+}
+
+/// Parse the class at the start of `pattern`, returning whether a character
+/// belongs to it and the length of the class in the pattern
+fn class(pattern: &[char]) -> Option<(impl Fn(char) -> bool, usize)> {
+    let mut idx = 1;
+    let negated = matches!(pattern.get(idx), Some('!' | '^'));
+    if negated {
+        idx += 1;
+    }
+    let mut ranges = vec![];
+    // A `]` right after the opening bracket is part of the set
+    let mut first = true;
+    loop {
+        let start = *pattern.get(idx)?;
+        if start == ']' && !first {
+            break;
+        }
+        first = false;
+        match (pattern.get(idx + 1), pattern.get(idx + 2)) {
+            (Some('-'), Some(&end)) if end != ']' => {
+                ranges.push((start, end));
+                idx += 3;
+            }
+            _ => {
+                ranges.push((start, start));
+                idx += 1;
+            }
+        }
+    }
+    let matcher = move |chr: char| {
+        ranges
+            .iter()
+            .any(|(start, end)| (*start..=*end).contains(&chr))
+            != negated
+    };
+    Some((matcher, idx + 1))
+}
+
+/// Whether `pattern` has a `*`, `?` or `[` that is not escaped
+fn has_magic(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(chr) = chars.next() {
+        match chr {
+            '\\' => {
+                chars.next();
+            }
+            '*' | '?' | '[' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+fn unescape(pattern: &str) -> String {
+    let mut text = String::new();
+    let mut chars = pattern.chars();
+    while let Some(chr) = chars.next() {
+        match chr {
+            '\\' => text.extend(chars.next()),
+            chr => text.push(chr),
+        }
+    }
+    text
+}
+
+/// `name` inside `dir`, where an empty `dir` is the working directory
+fn join(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else if dir.ends_with('/') {
+        format!("{}{}", dir, name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+fn dir_path(dir: &str) -> &str {
+    if dir.is_empty() {
+        "."
+    } else {
+        dir
+    }
+}
+
+/// Add the entries below `dir` to `paths`: every entry if `files`, otherwise only
+/// the directories, which don't include links to directories
+fn descendants(fs: &Filesystem, dir: &str, files: bool, paths: &mut Vec<String>) {
+    for entry in fs.list(dir_path(dir)).unwrap_or_default() {
+        if entry.name().starts_with('.') {
+            continue;
+        }
+        let path = join(dir, entry.name());
+        let is_dir = matches!(entry.ty(), LsResultType::Directory);
+        if files || is_dir {
+            paths.push(path.clone());
+        }
+        if is_dir {
+            descendants(fs, &path, files, paths);
+        }
+    }
+}
+
+/// The paths that match `pattern`, sorted. `*`, `?` and classes match within
+/// a component of the path and a `**` component matches any number of
+/// directories. Names starting with `.` only match a pattern starting with `.`.
+pub(crate) fn expand(pattern: &str, fs: &Filesystem) -> Vec<String> {
+    let (mut paths, rest) = match pattern.strip_prefix('/') {
+        Some(rest) => (vec!["/".to_string()], rest),
+        None => (vec![String::new()], pattern),
+    };
+    let components = rest.split('/').collect::<Vec<_>>();
+    for (idx, component) in components.iter().enumerate() {
+        let last = idx + 1 == components.len();
+        let mut next = vec![];
+        for path in &paths {
+            if component.is_empty() {
+                // Only directories are followed by a slash. The working directory
+                // itself, which `**/` starts from, has no name to put it after.
+                if fs.is_dir(dir_path(path)) && !(last && path.is_empty()) {
+                    next.push(if last {
+                        format!("{}/", path)
+                    } else {
+                        path.clone()
+                    });
+                }
+            } else if *component == "**" {
+                if !last {
+                    next.push(path.clone());
+                }
+                descendants(fs, path, last, &mut next);
+            } else if has_magic(component) {
+                let hidden = unescape(component).starts_with('.');
+                for entry in fs.list(dir_path(path)).unwrap_or_default() {
+                    if (hidden || !entry.name().starts_with('.'))
+                        && matches(component, entry.name())
+                    {
+                        next.push(join(path, entry.name()));
+                    }
+                }
+            } else {
+                next.push(join(path, &unescape(component)));
+            }
+        }
+        paths = next;
+    }
+    // Components without glob characters are only checked at the end
+    paths.retain(|path| fs.exists(path));
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// The arguments `field` stands for: the paths its pattern matches, or the field
+/// itself if it has no pattern or, like in bash, the pattern matches nothing.
+/// With `failglob` a pattern that matches nothing is an error instead.
+pub(crate) fn expand_field(
+    field: Field,
+    fs: &Filesystem,
+    failglob: bool,
+) -> Result<Vec<String>, String> {
+    let Some(pattern) = field.pattern else {
+        return Ok(vec![field.text]);
+    };
+    let paths = expand(&pattern, fs);
+    if !paths.is_empty() {
+        Ok(paths)
+    } else if failglob {
+        Err(format!("no match: {}", field.text))
+    } else {
+        Ok(vec![field.text])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LocalStorageSettings;
+
+    /// A filesystem with `a.md`, `b.txt`, `.hidden`, `sub/c.md` and `sub/.d.md`
+    /// in `/tmp/g`, which is the working directory
+    fn filesystem() -> Filesystem {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.mkdir("/tmp/g/sub", true).unwrap();
+        for file in ["a.md", "b.txt", ".hidden", "sub/c.md", "sub/.d.md"] {
+            fs.write_file(&format!("/tmp/g/{}", file), "", false)
+                .unwrap();
+        }
+        fs.cd("/tmp/g").unwrap();
+        fs
+    }
+
+    #[test]
+    fn wildcards_match_names() {
+        assert!(matches("*", "abc"));
+        assert!(matches("*", ""));
+        assert!(matches("a*c", "abbc"));
+        assert!(!matches("a*c", "abcd"));
+        assert!(matches("a?c", "abc"));
+        assert!(!matches("a?c", "ac"));
+        assert!(matches("\\*", "*"));
+        assert!(!matches("\\*", "a"));
+    }
+
+    #[test]
+    fn stars_do_not_backtrack_exponentially() {
+        let name = "a".repeat(100);
+        assert!(!matches("*a*a*a*a*a*a*a*a*b", &name));
+        assert!(matches("*a*a*a*a*a*a*a*a*a", &name));
+        assert!(matches("a*a*?", "aaa"));
+        assert!(!matches("a*a*?", "aa"));
+        assert!(matches("*.md", "a.b.md"));
+        assert!(matches("**", ""));
+    }
+
+    #[test]
+    fn classes_match_one_character() {
+        assert!(matches("[abc]", "b"));
+        assert!(!matches("[abc]", "d"));
+        assert!(matches("[a-c]x", "bx"));
+        assert!(!matches("[a-c]x", "dx"));
+        assert!(matches("[!a-c]", "d"));
+        assert!(matches("[^a-c]", "d"));
+        assert!(!matches("[!a-c]", "a"));
+        assert!(matches("[]]", "]"));
+        assert!(matches("[a-]", "-"));
+        // An unterminated class is a literal `[`
+        assert!(matches("[ab", "[ab"));
+        assert!(!matches("[ab", "a"));
+    }
+
+    #[test]
+    fn patterns_expand_to_sorted_paths() {
+        let fs = filesystem();
+        assert_eq!(expand("*", &fs), ["a.md", "b.txt", "sub"]);
+        assert_eq!(expand("*.md", &fs), ["a.md"]);
+        assert_eq!(expand("[ab].*", &fs), ["a.md", "b.txt"]);
+        assert_eq!(expand("/tmp/g/*/", &fs), ["/tmp/g/sub/"]);
+        assert_eq!(expand("/tmp/?/*.txt", &fs), ["/tmp/g/b.txt"]);
+        assert_eq!(expand("../g/sub/*", &fs), ["../g/sub/c.md"]);
+        assert!(expand("*.rs", &fs).is_empty());
+    }
+
+    #[test]
+    fn hidden_names_need_a_dot() {
+        let fs = filesystem();
+        assert_eq!(expand(".*", &fs), [".hidden"]);
+        assert_eq!(expand("sub/.*.md", &fs), ["sub/.d.md"]);
+        assert!(!expand("**", &fs).iter().any(|path| path.contains("/.")));
+    }
+
+    #[test]
+    fn double_stars_match_any_depth() {
+        let fs = filesystem();
+        assert_eq!(expand("**", &fs), ["a.md", "b.txt", "sub", "sub/c.md"]);
+        assert_eq!(expand("**/*.md", &fs), ["a.md", "sub/c.md"]);
+        assert_eq!(expand("/tmp/**/c.md", &fs), ["/tmp/g/sub/c.md"]);
+        assert_eq!(expand("**/", &fs), ["sub/"]);
+    }
+
+    #[test]
+    fn escaped_characters_are_literal() {
+        let mut fs = filesystem();
+        fs.write_file("*", "", false).unwrap();
+        assert_eq!(expand("\\*", &fs), ["*"]);
+        assert!(expand("\\*.md", &fs).is_empty());
+    }
+
+    #[test]
+    fn fields_without_matches_are_kept_unless_failglob() {
+        let fs = filesystem();
+        let field = |text: &str, pattern: Option<&str>| Field {
+            text: text.to_string(),
+            pattern: pattern.map(str::to_string),
+        };
+        assert_eq!(
+            expand_field(field("*.md", Some("*.md")), &fs, false),
+            Ok(vec!["a.md".to_string()])
+        );
+        assert_eq!(
+            expand_field(field("*.rs", Some("*.rs")), &fs, false),
+            Ok(vec!["*.rs".to_string()])
+        );
+        assert_eq!(
+            expand_field(field("*.rs", Some("*.rs")), &fs, true),
+            Err("no match: *.rs".to_string())
+        );
+        // A quoted pattern is never expanded, even with failglob
+        assert_eq!(
+            expand_field(field("*.rs", None), &fs, true),
+            Ok(vec!["*.rs".to_string()])
+        );
+    }
+}
@@ -44,6 +44,25 @@ impl History {
         &self.commands
     }
 
+    /// The most recent entry that continues `prefix`
+    pub(crate) fn suggest(&self, prefix: &str) -> Option<&String> {
+        self.commands
+            .iter()
+            .rev()
+            .find(|cmd| cmd.len() > prefix.len() && cmd.starts_with(prefix))
+    }
+
+    /// The index of the most recent entry before `before` that contains `query`.
+    /// Like bash, an empty query matches nothing.
+    pub(crate) fn search(&self, query: &str, before: usize) -> Option<usize> {
+        if query.is_empty() {
+            return None;
+        }
+        self.commands[..before.min(self.commands.len())]
+            .iter()
+            .rposition(|cmd| cmd.contains(query))
+    }
+
     pub(crate) fn clear(&mut self) {
         self.commands.clear();
         self.nav_pointer = 0;
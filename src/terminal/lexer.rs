@@ -0,0 +1,323 @@
+use std::{error::Error, fmt::Display, iter::Peekable, str::Chars};
+
+/// A piece of a word together with the quoting it was written in.
+/// Later expansion steps use the quoting to decide what they may touch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum WordPart {
+    /// Unquoted text
+    Bare(String),
+    /// Text inside single quotes or escaped with a backslash
+    Single(String),
+    /// Text inside double quotes
+    Double(String),
+}
+
+impl WordPart {
+    pub(crate) fn text(&self) -> &str {
+        match self {
+            WordPart::Bare(text) | WordPart::Single(text) | WordPart::Double(text) => text,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Word {
+    parts: Vec<WordPart>,
+}
+
+impl Word {
+    pub(crate) fn parts(&self) -> &[WordPart] {
+        &self.parts
+    }
+
+    fn push(&mut self, part: WordPart) {
+        // Empty parts only matter when they make up the whole word, as in `""`
+        if part.text().is_empty() && !self.parts.is_empty() {
+            return;
+        }
+        match (self.parts.last_mut(), part) {
+            (Some(WordPart::Bare(last)), WordPart::Bare(text))
+            | (Some(WordPart::Single(last)), WordPart::Single(text))
+            | (Some(WordPart::Double(last)), WordPart::Double(text)) => last.push_str(&text),
+            (_, part) => self.parts.push(part),
+        }
+    }
+
+    fn push_char(&mut self, chr: char, quoting: fn(String) -> WordPart) {
+        self.push(quoting(chr.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Token {
+    Word(Word),
+    /// `|`
+    Pipe,
+    /// `>`
+    Great,
+    /// `>>`
+    DGreat,
+    /// `<`
+    Less,
+    /// `;`
+    Semi,
+    /// `&&`
+    AndIf,
+    /// `||`
+    OrIf,
+    /// `&`, which is not supported
+    Amp,
+}
+
+impl Token {
+    /// How the token is written, for error messages
+    pub(crate) fn symbol(&self) -> &'static str {
+        match self {
+            Token::Word(_) => "word",
+            Token::Pipe => "|",
+            Token::Great => ">",
+            Token::DGreat => ">>",
+            Token::Less => "<",
+            Token::Semi => ";",
+            Token::AndIf => "&&",
+            Token::OrIf => "||",
+            Token::Amp => "&",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum LexError {
+    UnterminatedQuote(char),
+    TrailingEscape,
+}
+
+impl Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LexError::UnterminatedQuote(quote) => {
+                write!(f, "unexpected end of input: unterminated {} quote", quote)
+            }
+            LexError::TrailingEscape => write!(f, "unexpected end of input after \\"),
+        }
+    }
+}
+
+impl Error for LexError {}
+
+/// Split a command line into words and operators the way a POSIX shell does.
+/// Supports single and double quotes, backslash escapes and `#` comments.
+pub(crate) fn tokenize(input: &str) -> Result<Vec<Token>, LexError> {
+    let mut chars = input.chars().peekable();
+    let mut tokens = vec![];
+    let mut word = Word::default();
+    let mut in_word = false;
+
+    while let Some(chr) = chars.next() {
+        if in_word && (chr.is_whitespace() || is_operator(chr)) {
+            tokens.push(Token::Word(std::mem::take(&mut word)));
+            in_word = false;
+        }
+        match chr {
+            chr if chr.is_whitespace() => {}
+            '#' if !in_word => break,
+            '|' => {
+                if chars.next_if_eq(&'|').is_some() {
+                    tokens.push(Token::OrIf)
+                } else {
+                    tokens.push(Token::Pipe)
+                }
+            }
+            '&' => {
+                if chars.next_if_eq(&'&').is_some() {
+                    tokens.push(Token::AndIf)
+                } else {
+                    tokens.push(Token::Amp)
+                }
+            }
+            ';' => tokens.push(Token::Semi),
+            '>' => {
+                if chars.next_if_eq(&'>').is_some() {
+                    tokens.push(Token::DGreat)
+                } else {
+                    tokens.push(Token::Great)
+                }
+            }
+            '<' => tokens.push(Token::Less),
+            '$' if chars.peek() == Some(&'{') => {
+                // Keep `${VAR:-a b}` in one word, the expansion checks the braces
+                let mut param = chr.to_string();
+                for chr in chars.by_ref() {
+                    param.push(chr);
+                    if chr == '}' {
+                        break;
+                    }
+                }
+                word.push(WordPart::Bare(param));
+                in_word = true;
+            }
+            '\\' => {
+                let escaped = chars.next().ok_or(LexError::TrailingEscape)?;
+                word.push_char(escaped, WordPart::Single);
+                in_word = true;
+            }
+            '\'' => {
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(chr) => quoted.push(chr),
+                        None => return Err(LexError::UnterminatedQuote('\'')),
+                    }
+                }
+                word.push(WordPart::Single(quoted));
+                in_word = true;
+            }
+            '"' => {
+                read_double_quoted(&mut chars, &mut word)?;
+                in_word = true;
+            }
+            chr => {
+                word.push_char(chr, WordPart::Bare);
+                in_word = true;
+            }
+        }
+    }
+
+    if in_word {
+        tokens.push(Token::Word(word));
+    }
+
+    Ok(tokens)
+}
+
+fn is_operator(chr: char) -> bool {
+    matches!(chr, '|' | '>' | '<' | ';' | '&')
+}
+
+fn read_double_quoted(chars: &mut Peekable<Chars>, word: &mut Word) -> Result<(), LexError> {
+    let mut quoted = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => break,
+            Some('\\') => match chars.peek() {
+                Some(&escaped @ ('"' | '\\' | '$' | '`')) => {
+                    chars.next();
+                    if !quoted.is_empty() {
+                        word.push(WordPart::Double(std::mem::take(&mut quoted)));
+                    }
+                    word.push_char(escaped, WordPart::Single);
+                }
+                Some(_) => quoted.push('\\'),
+                None => return Err(LexError::UnterminatedQuote('"')),
+            },
+            Some(chr) => quoted.push(chr),
+            None => return Err(LexError::UnterminatedQuote('"')),
+        }
+    }
+    word.push(WordPart::Double(quoted));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(input: &str) -> Vec<Vec<WordPart>> {
+        tokenize(input)
+            .unwrap()
+            .into_iter()
+            .map(|token| match token {
+                Token::Word(word) => word.parts,
+                token => panic!("not a word: {:?}", token),
+            })
+            .collect()
+    }
+
+    fn bare(text: &str) -> WordPart {
+        WordPart::Bare(text.to_string())
+    }
+
+    fn single(text: &str) -> WordPart {
+        WordPart::Single(text.to_string())
+    }
+
+    fn double(text: &str) -> WordPart {
+        WordPart::Double(text.to_string())
+    }
+
+    #[test]
+    fn quotes_keep_their_quoting() {
+        assert_eq!(
+            words("echo 'a b' \"c $d\" e'f'\"g\""),
+            vec![
+                vec![bare("echo")],
+                vec![single("a b")],
+                vec![double("c $d")],
+                vec![bare("e"), single("f"), double("g")],
+            ]
+        );
+        assert_eq!(words("''"), vec![vec![single("")]]);
+        assert_eq!(words("a''"), vec![vec![bare("a")]]);
+    }
+
+    #[test]
+    fn backslashes_escape_one_character() {
+        assert_eq!(
+            words("a\\ b"),
+            vec![vec![bare("a"), single(" "), bare("b")]]
+        );
+        assert_eq!(words("\\|"), vec![vec![single("|")]]);
+        // Inside double quotes only `"`, `\`, `$` and `` ` `` are escaped
+        assert_eq!(
+            words("\"a\\\"b\\n\""),
+            vec![vec![double("a"), single("\""), double("b\\n")]]
+        );
+    }
+
+    #[test]
+    fn comments_only_start_at_a_word() {
+        assert_eq!(
+            words("echo a # b c"),
+            vec![vec![bare("echo")], vec![bare("a")]]
+        );
+        assert_eq!(words("a#b"), vec![vec![bare("a#b")]]);
+        assert_eq!(
+            words("'#' \"#\""),
+            vec![vec![single("#")], vec![double("#")]]
+        );
+        assert!(words("# all of it").is_empty());
+    }
+
+    #[test]
+    fn unterminated_input_is_an_error() {
+        assert_eq!(tokenize("'abc"), Err(LexError::UnterminatedQuote('\'')));
+        assert_eq!(
+            tokenize("echo \"abc"),
+            Err(LexError::UnterminatedQuote('"'))
+        );
+        assert_eq!(tokenize("\"abc\\"), Err(LexError::UnterminatedQuote('"')));
+        assert_eq!(tokenize("abc\\"), Err(LexError::TrailingEscape));
+    }
+
+    #[test]
+    fn operators_split_words() {
+        let symbols = tokenize("a|b||c&&d;e>f>>g<h&")
+            .unwrap()
+            .iter()
+            .map(|token| match token {
+                Token::Word(word) => word.parts[0].text().to_string(),
+                token => token.symbol().to_string(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            symbols,
+            ["a", "|", "b", "||", "c", "&&", "d", ";", "e", ">", "f", ">>", "g", "<", "h", "&"]
+        );
+    }
+
+    #[test]
+    fn braced_parameters_stay_in_one_word() {
+        assert_eq!(words("${A:-a b}c"), vec![vec![bare("${A:-a b}c")]]);
+    }
+}
@@ -1,4 +1,16 @@
+pub(crate) mod alias;
 pub(crate) mod buffer;
 pub(crate) mod commands;
+pub(crate) mod completion;
+pub(crate) mod environment;
+pub(crate) mod expand;
 pub(crate) mod filesystem;
+pub(crate) mod glob;
 pub(crate) mod history;
+pub(crate) mod lexer;
+pub(crate) mod overlay;
+pub(crate) mod parser;
+pub(crate) mod path;
+pub(crate) mod system;
+pub(crate) mod time;
+pub(crate) mod users;
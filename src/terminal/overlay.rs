@@ -0,0 +1,74 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::{filesystem::now, system::Dynamic};
+
+/// A node created or changed by the user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum OverlayEntry {
+    Directory,
+    File(String),
+    /// A link to the path, as it was given
+    Link(String),
+    Dynamic(Dynamic),
+}
+
+/// The permissions and modification time of a node created or changed by the user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Attributes {
+    pub(crate) mode: u32,
+    pub(crate) owner: String,
+    pub(crate) group: String,
+    /// In seconds since the Unix epoch. Saves from before it was kept count as new.
+    #[serde(default = "now")]
+    pub(crate) modified: u64,
+}
+
+/// The changes made to the built-in filesystem, by absolute path. This is what
+/// is saved in the local storage: the built-in content itself is never stored,
+/// so it can be updated without losing the visitor's files.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Overlay {
+    /// Built-in nodes that were removed, with everything below them
+    removed: BTreeSet<String>,
+    /// Nodes created or changed by the user. A parent sorts before its children.
+    entries: BTreeMap<String, OverlayEntry>,
+    /// The mode, owner, group and modification time of the nodes created or changed by the user
+    attributes: BTreeMap<String, Attributes>,
+}
+
+impl Overlay {
+    pub(crate) fn removed(&self) -> &BTreeSet<String> {
+        &self.removed
+    }
+
+    pub(crate) fn entries(&self) -> &BTreeMap<String, OverlayEntry> {
+        &self.entries
+    }
+
+    pub(crate) fn attributes(&self) -> &BTreeMap<String, Attributes> {
+        &self.attributes
+    }
+
+    pub(crate) fn set(&mut self, path: String, entry: OverlayEntry) {
+        self.entries.insert(path, entry);
+    }
+
+    pub(crate) fn set_attributes(&mut self, path: String, attributes: Attributes) {
+        self.attributes.insert(path, attributes);
+    }
+
+    /// Forget the changes at and below `path`. If it is a built-in node, hide it.
+    pub(crate) fn remove(&mut self, path: &str, builtin: bool) {
+        let below = format!("{}/", path);
+        self.entries
+            .retain(|entry, _| entry != path && !entry.starts_with(&below));
+        self.attributes
+            .retain(|entry, _| entry != path && !entry.starts_with(&below));
+        if builtin {
+            self.removed.insert(path.to_string());
+        }
+    }
+}
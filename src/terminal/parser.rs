@@ -0,0 +1,262 @@
+use std::{error::Error, fmt::Display};
+
+use super::{
+    alias::Aliases,
+    lexer::{self, LexError, Token, Word},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RedirectKind {
+    /// `> file`
+    Output,
+    /// `>> file`
+    Append,
+    /// `< file`
+    Input,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Redirect {
+    pub(crate) kind: RedirectKind,
+    pub(crate) target: Word,
+}
+
+/// A single command with its arguments, `argv[0]` being the command name
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SimpleCommand {
+    pub(crate) words: Vec<Word>,
+    pub(crate) redirects: Vec<Redirect>,
+}
+
+impl SimpleCommand {
+    fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.redirects.is_empty()
+    }
+}
+
+/// Commands connected with `|`. The stdout of each stage is the stdin of the next.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Pipeline {
+    pub(crate) stages: Vec<SimpleCommand>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ParseError {
+    Lex(LexError),
+    UnexpectedToken(&'static str),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Lex(err) => write!(f, "{}", err),
+            ParseError::UnexpectedToken(token) => {
+                write!(f, "syntax error near unexpected token: {}", token)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<LexError> for ParseError {
+    fn from(value: LexError) -> Self {
+        ParseError::Lex(value)
+    }
+}
+
+/// How a pipeline is joined to the one before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Connector {
+    /// `;` or the start of the line: always run
+    Always,
+    /// `&&`: run if the previous pipeline succeeded
+    And,
+    /// `||`: run if the previous pipeline failed
+    Or,
+}
+
+/// Pipelines separated by `;`, `&&` and `||`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CommandList {
+    pub(crate) items: Vec<(Connector, Pipeline)>,
+}
+
+/// Parse a command line, expanding aliases. An empty line yields an empty list.
+pub(crate) fn parse(input: &str, aliases: &Aliases) -> Result<CommandList, ParseError> {
+    let mut tokens = aliases.expand(lexer::tokenize(input)?)?.into_iter();
+    let mut list = CommandList::default();
+    let mut connector = Connector::Always;
+    let mut pipeline = Pipeline::default();
+    let mut current = SimpleCommand::default();
+
+    while let Some(token) = tokens.next() {
+        let kind = match token {
+            Token::Word(word) => {
+                current.words.push(word);
+                continue;
+            }
+            Token::Pipe => {
+                if current.is_empty() {
+                    return Err(ParseError::UnexpectedToken("|"));
+                }
+                pipeline.stages.push(std::mem::take(&mut current));
+                continue;
+            }
+            Token::Semi | Token::AndIf | Token::OrIf => {
+                if current.is_empty() {
+                    return Err(ParseError::UnexpectedToken(token.symbol()));
+                }
+                pipeline.stages.push(std::mem::take(&mut current));
+                list.items.push((connector, std::mem::take(&mut pipeline)));
+                connector = match token {
+                    Token::AndIf => Connector::And,
+                    Token::OrIf => Connector::Or,
+                    _ => Connector::Always,
+                };
+                continue;
+            }
+            Token::Amp => return Err(ParseError::UnexpectedToken("&")),
+            Token::Great => RedirectKind::Output,
+            Token::DGreat => RedirectKind::Append,
+            Token::Less => RedirectKind::Input,
+        };
+        match tokens.next() {
+            Some(Token::Word(target)) => current.redirects.push(Redirect { kind, target }),
+            Some(other) => return Err(ParseError::UnexpectedToken(other.symbol())),
+            None => return Err(ParseError::UnexpectedToken("newline")),
+        }
+    }
+
+    if current.is_empty() {
+        // A trailing `;` is fine, anything else still expects a command
+        if !pipeline.stages.is_empty() {
+            return Err(ParseError::UnexpectedToken("|"));
+        }
+        if connector != Connector::Always {
+            return Err(ParseError::UnexpectedToken("newline"));
+        }
+    } else {
+        pipeline.stages.push(current);
+        list.items.push((connector, pipeline));
+    }
+
+    Ok(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{terminal::lexer::WordPart, LocalStorageSettings};
+
+    fn parse(input: &str) -> Result<CommandList, ParseError> {
+        super::parse(input, &Aliases::from(&LocalStorageSettings::default()))
+    }
+
+    fn text(word: &Word) -> String {
+        word.parts().iter().map(WordPart::text).collect()
+    }
+
+    /// The words of every stage of every pipeline
+    fn stages(list: &CommandList) -> Vec<Vec<Vec<String>>> {
+        list.items
+            .iter()
+            .map(|(_, pipeline)| {
+                pipeline
+                    .stages
+                    .iter()
+                    .map(|stage| stage.words.iter().map(text).collect())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pipes_join_stages() {
+        let list = parse("ls -l | grep a | wc -l").unwrap();
+        assert_eq!(
+            stages(&list),
+            vec![vec![vec!["ls", "-l"], vec!["grep", "a"], vec!["wc", "-l"]]]
+        );
+    }
+
+    #[test]
+    fn connectors_separate_pipelines() {
+        let list = parse("a; b && c || d | e").unwrap();
+        let connectors = list
+            .items
+            .iter()
+            .map(|(connector, _)| *connector)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            connectors,
+            [
+                Connector::Always,
+                Connector::Always,
+                Connector::And,
+                Connector::Or
+            ]
+        );
+        assert_eq!(
+            stages(&list),
+            vec![
+                vec![vec!["a"]],
+                vec![vec!["b"]],
+                vec![vec!["c"]],
+                vec![vec!["d"], vec!["e"]]
+            ]
+        );
+    }
+
+    #[test]
+    fn redirects_are_taken_out_of_the_words() {
+        let list = parse("cat < in a > out b >> 'log file'").unwrap();
+        assert_eq!(stages(&list), vec![vec![vec!["cat", "a", "b"]]]);
+        let redirects = list.items[0].1.stages[0]
+            .redirects
+            .iter()
+            .map(|redirect| (redirect.kind, text(&redirect.target)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            redirects,
+            [
+                (RedirectKind::Input, "in".to_string()),
+                (RedirectKind::Output, "out".to_string()),
+                (RedirectKind::Append, "log file".to_string())
+            ]
+        );
+        // A command may be only a redirect, like `> file` to empty a file
+        assert!(parse("> file").is_ok());
+    }
+
+    #[test]
+    fn empty_lines_and_trailing_semicolons_are_fine() {
+        assert!(parse("").unwrap().items.is_empty());
+        assert!(parse("  # comment").unwrap().items.is_empty());
+        assert_eq!(stages(&parse("a;").unwrap()), vec![vec![vec!["a"]]]);
+    }
+
+    #[test]
+    fn missing_commands_are_syntax_errors() {
+        let unexpected = |input| match parse(input) {
+            Err(ParseError::UnexpectedToken(token)) => token,
+            res => panic!("{:?} parsed to {:?}", input, res),
+        };
+        assert_eq!(unexpected("| a"), "|");
+        assert_eq!(unexpected("a |"), "|");
+        assert_eq!(unexpected("a && && b"), "&&");
+        assert_eq!(unexpected("; a"), ";");
+        assert_eq!(unexpected("a ||"), "newline");
+        assert_eq!(unexpected("a >"), "newline");
+        assert_eq!(unexpected("a > | b"), "|");
+        assert_eq!(unexpected("a &"), "&");
+    }
+
+    #[test]
+    fn lex_errors_are_reported() {
+        assert_eq!(
+            parse("echo 'a").unwrap_err(),
+            ParseError::Lex(LexError::UnterminatedQuote('\''))
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt::Display;
+
+/// An absolute, normalized path in the virtual filesystem.
+///
+/// Parsing resolves `.`, `..` and repeated slashes against the working
+/// directory, so the path only holds the names to walk from the root. Like the
+/// logical paths of a shell, `..` removes the previous name instead of going
+/// to the parent of whatever a link pointed to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct VirtualPath {
+    components: Vec<String>,
+    /// Whether the path must name a directory, as with a trailing `/`, `.` or `..`
+    dir: bool,
+}
+
+impl VirtualPath {
+    pub(crate) fn root() -> Self {
+        Self::default()
+    }
+
+    /// Resolve `path` against `cwd`. A `~` is an ordinary name, the shell
+    /// expands it before.
+    pub(crate) fn parse(path: &str, cwd: &VirtualPath) -> Self {
+        let (base, rest) = if path.starts_with('/') {
+            (Self::root(), path)
+        } else {
+            (cwd.clone(), path)
+        };
+
+        let mut resolved = Self { dir: false, ..base };
+        for name in rest.split('/') {
+            match name {
+                "" => {}
+                "." => {}
+                ".." => {
+                    resolved.components.pop();
+                }
+                name => resolved.components.push(name.to_string()),
+            }
+        }
+        let last = rest.rsplit('/').next().unwrap_or_default();
+        resolved.dir = rest.ends_with('/') || last == "." || last == "..";
+        resolved
+    }
+
+    pub(crate) fn components(&self) -> &[String] {
+        &self.components
+    }
+
+    /// The last name of the path, `None` for the root
+    pub(crate) fn file_name(&self) -> Option<&str> {
+        self.components.last().map(String::as_str)
+    }
+
+    /// The directory containing the path, `None` for the root
+    pub(crate) fn parent(&self) -> Option<VirtualPath> {
+        let (_, parent) = self.components.split_last()?;
+        Some(Self {
+            components: parent.to_vec(),
+            dir: true,
+        })
+    }
+
+    pub(crate) fn join(&self, name: &str) -> VirtualPath {
+        let mut components = self.components.clone();
+        components.push(name.to_string());
+        Self {
+            components,
+            dir: false,
+        }
+    }
+
+    pub(crate) fn is_dir(&self) -> bool {
+        self.dir
+    }
+}
+
+impl Display for VirtualPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.components.is_empty() {
+            return write!(f, "/");
+        }
+        for name in &self.components {
+            write!(f, "/{}", name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(path: &str) -> VirtualPath {
+        let cwd = VirtualPath::parse("/home/guest/projects", &VirtualPath::root());
+        VirtualPath::parse(path, &cwd)
+    }
+
+    #[test]
+    fn dots_are_normalized() {
+        assert_eq!(parse(".").to_string(), "/home/guest/projects");
+        assert_eq!(parse("..").to_string(), "/home/guest");
+        assert_eq!(parse("../..").to_string(), "/home");
+        assert_eq!(parse("./a/../b/.").to_string(), "/home/guest/projects/b");
+        assert_eq!(parse("/a//b/./c/..").to_string(), "/a/b");
+    }
+
+    #[test]
+    fn the_root_has_no_parent() {
+        assert_eq!(parse("/..").to_string(), "/");
+        assert_eq!(parse("../../../../..").to_string(), "/");
+        assert_eq!(parse("/").file_name(), None);
+        assert!(parse("/").parent().is_none());
+    }
+
+    #[test]
+    fn the_tilde_is_an_ordinary_name() {
+        assert_eq!(parse("~").to_string(), "/home/guest/projects/~");
+        assert_eq!(parse("~/a").to_string(), "/home/guest/projects/~/a");
+        assert_eq!(parse("a/~").to_string(), "/home/guest/projects/a/~");
+        assert_eq!(parse("~a").to_string(), "/home/guest/projects/~a");
+    }
+
+    #[test]
+    fn trailing_slashes_and_dots_name_directories() {
+        assert!(parse("a/").is_dir());
+        assert!(parse("a/.").is_dir());
+        assert!(parse("a/..").is_dir());
+        assert!(!parse("a").is_dir());
+        assert!(!parse("a/./b").is_dir());
+    }
+
+    #[test]
+    fn parents_and_names() {
+        let path = parse("/a/b/c");
+        assert_eq!(path.file_name(), Some("c"));
+        assert_eq!(path.parent().unwrap().to_string(), "/a/b");
+        assert_eq!(path.components(), ["a", "b", "c"]);
+        assert_eq!(parse("/a").join("d").to_string(), "/a/d");
+    }
+}
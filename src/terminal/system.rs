@@ -0,0 +1,221 @@
+//! The files of `/etc`, `/proc` and `/dev` whose contents are computed when
+//! they are read, and `/tmp`, which is never saved.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Instant,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+/// The name of the machine, as the prompt shows it
+pub(crate) const HOSTNAME: &str = "aureliobuonomo.it";
+
+/// The directory whose contents only last for the session
+pub(crate) const TMP: &str = "/tmp";
+
+/// The directories the system files live in, with their modes
+pub(crate) const DIRECTORIES: &[(&str, u32)] = &[
+    ("/dev", 0o755),
+    ("/etc", 0o755),
+    ("/proc", 0o555),
+    (TMP, 0o777),
+];
+
+/// The dynamic files, with their modes
+pub(crate) const FILES: &[(&str, Dynamic, u32)] = &[
+    ("/dev/null", Dynamic::Null, 0o666),
+    ("/dev/urandom", Dynamic::Urandom, 0o666),
+    ("/etc/hostname", Dynamic::Hostname, 0o644),
+    ("/etc/motd", Dynamic::Motd, 0o644),
+    ("/etc/os-release", Dynamic::OsRelease, 0o644),
+    ("/proc/cpuinfo", Dynamic::CpuInfo, 0o444),
+    ("/proc/uptime", Dynamic::Uptime, 0o444),
+    ("/proc/version", Dynamic::Version, 0o444),
+];
+
+/// How many bytes a read of `/dev/urandom` returns
+const RANDOM_BYTES: usize = 512;
+
+#[cfg(not(target_arch = "wasm32"))]
+lazy_static! {
+    static ref STARTED: Instant = Instant::now();
+}
+
+/// A file whose contents are computed every time it is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Dynamic {
+    Motd,
+    Hostname,
+    OsRelease,
+    /// The time since the page was loaded
+    Uptime,
+    /// The version of the crate
+    Version,
+    /// The processors the browser reports
+    CpuInfo,
+    Null,
+    Urandom,
+}
+
+impl Dynamic {
+    pub(crate) fn contents(self) -> String {
+        match self {
+            Self::Motd => format!(
+                "Welcome to {}!\n\nType `help` to list the commands and `cat welcome.md` to get started.\n",
+                HOSTNAME
+            ),
+            Self::Hostname => format!("{}\n", HOSTNAME),
+            Self::OsRelease => format!(
+                "NAME=\"Portfolio\"\nVERSION=\"{version}\"\nID={name}\nPRETTY_NAME=\"Portfolio {version}\"\nHOME_URL=\"https://{host}/\"\n",
+                name = env!("CARGO_PKG_NAME"),
+                version = env!("CARGO_PKG_VERSION"),
+                host = HOSTNAME
+            ),
+            Self::Uptime => {
+                let uptime = uptime();
+                format!("{:.2} {:.2}\n", uptime, uptime)
+            }
+            Self::Version => format!(
+                "{} version {} ({}) #1\n",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION"),
+                env!("CARGO_PKG_AUTHORS")
+            ),
+            Self::CpuInfo => cpuinfo(),
+            Self::Null => String::new(),
+            // Files hold text, so the bytes are kept to 7 bits: as ASCII characters
+            // they are one byte each in UTF-8, and `head -c` and `wc -c` count them right
+            Self::Urandom => (0..RANDOM_BYTES)
+                .map(|_| char::from(random_byte() & 0x7f))
+                .collect(),
+        }
+    }
+
+    /// Whether the file is a device, which `ls` marks with a `c`
+    pub(crate) fn is_device(self) -> bool {
+        matches!(self, Self::Null | Self::Urandom)
+    }
+
+    /// The size `ls` shows. Like on Linux, devices and the files of `/proc` are empty.
+    pub(crate) fn size(self) -> u64 {
+        match self {
+            Self::Motd | Self::Hostname | Self::OsRelease => self.contents().len() as u64,
+            _ => 0,
+        }
+    }
+}
+
+/// Whether `path`, an absolute path, is `/tmp` or inside it
+pub(crate) fn is_temporary(path: &str) -> bool {
+    path.strip_prefix(TMP)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Seconds since the page was loaded
+fn uptime() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    return web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or(0.0, |performance| performance.now() / 1000.0);
+    #[cfg(not(target_arch = "wasm32"))]
+    return STARTED.elapsed().as_secs_f64();
+}
+
+/// One block per processor, like `/proc/cpuinfo`
+fn cpuinfo() -> String {
+    #[cfg(target_arch = "wasm32")]
+    let (cores, platform, model) = match web_sys::window().map(|window| window.navigator()) {
+        Some(navigator) => (
+            navigator.hardware_concurrency() as usize,
+            navigator.platform().unwrap_or_default(),
+            navigator.user_agent().unwrap_or_default(),
+        ),
+        None => (1, String::new(), String::new()),
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    let (cores, platform, model) = (
+        std::thread::available_parallelism().map_or(1, |cores| cores.get()),
+        std::env::consts::ARCH.to_string(),
+        "unknown".to_string(),
+    );
+    (0..cores.max(1))
+        .map(|processor| {
+            format!(
+                "processor\t: {}\nvendor_id\t: {}\nmodel name\t: {}\ncpu cores\t: {}\n\n",
+                processor, platform, model, cores
+            )
+        })
+        .collect()
+}
+
+fn random_byte() -> u8 {
+    #[cfg(target_arch = "wasm32")]
+    return (web_sys::js_sys::Math::random() * 256.0) as u8;
+    #[cfg(not(target_arch = "wasm32"))]
+    return RandomState::new().build_hasher().finish() as u8;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        terminal::{filesystem::Filesystem, overlay::Overlay},
+        LocalStorageSettings,
+    };
+
+    #[test]
+    fn devices_read_as_nothing_or_random_ascii() {
+        let fs = Filesystem::from(&LocalStorageSettings::default());
+        assert_eq!(fs.read_file("/dev/null").unwrap(), "");
+        let random = fs.read_file("/dev/urandom").unwrap();
+        assert_eq!(random.len(), RANDOM_BYTES);
+        assert!(random.is_ascii());
+        assert_eq!(fs.entry("/dev/null").unwrap().size(), Some(0));
+    }
+
+    #[test]
+    fn proc_files_are_computed_when_read() {
+        let fs = Filesystem::from(&LocalStorageSettings::default());
+        let uptime = fs.read_file("/proc/uptime").unwrap();
+        let seconds = uptime.split_whitespace().collect::<Vec<_>>();
+        assert_eq!(seconds.len(), 2);
+        assert!(seconds.iter().all(|value| value.parse::<f64>().is_ok()));
+        assert!(fs
+            .read_file("/proc/version")
+            .unwrap()
+            .contains(env!("CARGO_PKG_VERSION")));
+        assert!(fs
+            .read_file("/proc/cpuinfo")
+            .unwrap()
+            .starts_with("processor\t: 0\n"));
+        assert_eq!(
+            fs.read_file("/etc/hostname").unwrap(),
+            format!("{}\n", HOSTNAME)
+        );
+    }
+
+    #[test]
+    fn only_devices_can_be_written() {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.write_file("/dev/null", "gone", false).unwrap();
+        assert_eq!(fs.read_file("/dev/null").unwrap(), "");
+        assert!(fs.write_file("/proc/uptime", "0", false).is_err());
+        assert!(fs.write_file("/etc/hostname", "x", false).is_err());
+    }
+
+    #[test]
+    fn tmp_is_not_saved() {
+        let mut fs = Filesystem::from(&LocalStorageSettings::default());
+        fs.write_file("/tmp/a", "a", false).unwrap();
+        fs.mkdir("/tmp/b", false).unwrap();
+        assert_eq!(*fs.overlay(), Overlay::default());
+        assert!(is_temporary("/tmp"));
+        assert!(is_temporary("/tmp/a"));
+        assert!(!is_temporary("/tmpfile"));
+    }
+}
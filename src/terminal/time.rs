@@ -0,0 +1,67 @@
+//! Formatting timestamps, in seconds since the Unix epoch. Times are shown in UTC.
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// About six months, after which `ls` shows the year instead of the time
+const RECENT: u64 = 182 * 24 * 60 * 60;
+
+struct DateTime {
+    year: u64,
+    /// From 1
+    month: u64,
+    day: u64,
+    hour: u64,
+    minute: u64,
+    second: u64,
+}
+
+impl DateTime {
+    fn from_timestamp(secs: u64) -> Self {
+        let (days, time) = (secs / 86400, secs % 86400);
+        // Civil from days, see http://howardhinnant.github.io/date_algorithms.html
+        let days = days + 719468;
+        let era = days / 146097;
+        let day_of_era = days % 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month + 2) / 5 + 1;
+        let month = if month < 10 { month + 3 } else { month - 9 };
+        let year = year_of_era + era * 400 + u64::from(month <= 2);
+        Self {
+            year,
+            month,
+            day,
+            hour: time / 3600,
+            minute: time % 3600 / 60,
+            second: time % 60,
+        }
+    }
+}
+
+/// Like `2024-05-01 12:00:00 UTC`
+pub(crate) fn format_full(secs: u64) -> String {
+    let time = DateTime::from_timestamp(secs);
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        time.year, time.month, time.day, time.hour, time.minute, time.second
+    )
+}
+
+/// Like `ls -l`: `May  1 12:00`, or `May  1  2023` for times more than six
+/// months before `now` or in the future
+pub(crate) fn format_short(secs: u64, now: u64) -> String {
+    let time = DateTime::from_timestamp(secs);
+    let month = MONTHS[time.month as usize - 1];
+    if secs > now || now - secs > RECENT {
+        format!("{} {:>2}  {}", month, time.day, time.year)
+    } else {
+        format!(
+            "{} {:>2} {:02}:{:02}",
+            month, time.day, time.hour, time.minute
+        )
+    }
+}
@@ -0,0 +1,86 @@
+//! The accounts of `/etc/passwd`, `/etc/group` and `/etc/shadow`. The files are
+//! read like the system reads them, whatever their permissions.
+
+use super::filesystem::Filesystem;
+
+pub(crate) const PASSWD: &str = "/etc/passwd";
+pub(crate) const GROUP: &str = "/etc/group";
+pub(crate) const SHADOW: &str = "/etc/shadow";
+
+/// A line of `/etc/passwd`: `name:x:uid:gid:comment:home:shell`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct User {
+    pub(crate) name: String,
+    pub(crate) uid: u32,
+    pub(crate) gid: u32,
+    pub(crate) home: String,
+}
+
+/// A line of `/etc/group`: `name:x:gid:member,member`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Group {
+    pub(crate) name: String,
+    pub(crate) gid: u32,
+    pub(crate) members: Vec<String>,
+}
+
+/// The fields of the lines of a `:` separated file, skipping comments and blank lines
+fn records<'a>(fs: &'a Filesystem, path: &str) -> impl Iterator<Item = Vec<&'a str>> {
+    fs.system_file(path)
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| line.split(':').collect())
+}
+
+pub(crate) fn users(fs: &Filesystem) -> Vec<User> {
+    records(fs, PASSWD)
+        .filter_map(|fields| match fields.as_slice() {
+            [name, _, uid, gid, _, home, ..] => Some(User {
+                name: name.to_string(),
+                uid: uid.parse().ok()?,
+                gid: gid.parse().ok()?,
+                home: home.to_string(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+pub(crate) fn groups(fs: &Filesystem) -> Vec<Group> {
+    records(fs, GROUP)
+        .filter_map(|fields| match fields.as_slice() {
+            [name, _, gid, members, ..] => Some(Group {
+                name: name.to_string(),
+                gid: gid.parse().ok()?,
+                members: members
+                    .split(',')
+                    .filter(|member| !member.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+pub(crate) fn user(fs: &Filesystem, name: &str) -> Option<User> {
+    users(fs).into_iter().find(|user| user.name == name)
+}
+
+/// The groups `user` belongs to, its primary group first
+pub(crate) fn groups_of(fs: &Filesystem, user: &User) -> Vec<Group> {
+    let mut groups = groups(fs);
+    groups.retain(|group| group.gid == user.gid || group.members.contains(&user.name));
+    groups.sort_by_key(|group| group.gid != user.gid);
+    groups
+}
+
+/// The password of `name` from `/etc/shadow`: empty if there is none and `None`
+/// if the account is locked
+pub(crate) fn password(fs: &Filesystem, name: &str) -> Option<String> {
+    records(fs, SHADOW)
+        .find(|fields| fields.first() == Some(&name))
+        .and_then(|fields| fields.get(1).map(|password| password.to_string()))
+        .filter(|password| !password.starts_with('!'))
+}
@@ -9,10 +9,10 @@ mod prompt;
 pub(crate) use prompt::Prompt;
 
 #[component]
-pub(crate) fn CmdOutput(cmd: String, cmd_output: String, failed: bool) -> Element {
+pub(crate) fn CmdOutput(cmd: String, cmd_output: String, status: i32) -> Element {
     rsx! {
         div {
-            SimplePromptText { command_failed: failed }
+            SimplePromptText { status }
             "{cmd}"
         }
         div { class: "cmd-output", dangerous_inner_html: cmd_output }
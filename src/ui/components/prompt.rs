@@ -1,7 +1,21 @@
 use dioxus::core_macro::component;
 use dioxus::prelude::*;
+use web_sys::{wasm_bindgen::JsCast, HtmlInputElement};
 
-use crate::AppState;
+use crate::{
+    terminal::{commands::su, completion, system::HOSTNAME},
+    AppState,
+};
+
+/// State of a Ctrl+R reverse incremental history search
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Search {
+    query: String,
+    /// Index of the current match in the history
+    found: Option<usize>,
+    /// The line that was being edited when the search started
+    original: String,
+}
 
 #[component]
 pub(crate) fn Prompt() -> Element {
@@ -9,36 +23,162 @@ pub(crate) fn Prompt() -> Element {
     let mut command = use_signal(|| "".to_string());
     let mut typed = use_signal(|| "".to_string());
     let mut hidden_buf = use_signal(|| "".to_string());
+    let mut completions = use_signal(Vec::<String>::new);
+    let mut tab_pressed = use_signal(|| false);
+    let mut search = use_signal(|| None::<Search>);
+    let mut password = use_signal(|| "".to_string());
+
+    let (status, asking_password, theme) = {
+        let state = state.read();
+        (state.status(), state.pending_su().is_some(), *state.theme())
+    };
+
+    let mut submit = move |line: String| {
+        crate::terminal::commands::exec(line.clone());
+        state.write().history_mut().push(line);
+        state.write().update_history();
+        command.set("".to_string());
+        typed.set("".to_string());
+        hidden_buf.set("".to_string());
+    };
 
-    let last_failed = state()
-        .buffer()
-        .commands()
-        .last()
-        .map_or(false, |cmd| cmd.failed);
+    let suggestion = if search().is_none() && !command().is_empty() {
+        let state = state.read();
+        let line = command();
+        state
+            .history()
+            .suggest(&line)
+            .cloned()
+            .or_else(|| completion::suggest(&line, state.fs(), state.env(), state.aliases()))
+    } else {
+        None
+    };
+
+    let found = search().and_then(|search| {
+        search
+            .found
+            .and_then(|idx| state.read().history().entries().get(idx).cloned())
+            .map(|entry| (entry, search.query))
+    });
 
     rsx! {
         div { id: "prompt-container",
             div { id: "prompt",
-                PromptText { command_failed: last_failed }
+                if search().is_some() {
+                    span { style: "white-space: pre", "(reverse-i-search)`" }
+                } else if asking_password {
+                    span { style: "white-space: pre", "Password: " }
+                } else {
+                    PromptText { status }
+                }
             }
             div { id: "input-container",
+                if let Some(suggestion) = &suggestion {
+                    div { id: "suggestion",
+                        span { style: "visibility: hidden", "{command}" }
+                        span { style: "color: {theme.text.hex()}", {suggestion[command().len()..].to_string()} }
+                    }
+                }
                 input {
                     id: "input",
-                    style: "color: {state().theme().text.hex()}",
-                    value: command,
-                    oninput: move |event| command.set(event.value()),
+                    style: "color: {theme.text.hex()}",
+                    r#type: if asking_password { "password" } else { "text" },
+                    value: if asking_password { password() } else { search().map_or(command(), |search| search.query) },
+                    onmounted: |_| {
+                        // Keep the browser from reloading or searching the page
+                        eval(
+                            r#"
+                            document.getElementById('input').addEventListener('keydown', (e) => {
+                                if (e.ctrlKey && (e.key === 'r' || e.key === 'g')) e.preventDefault()
+                            })
+                            "#,
+                        );
+                    },
+                    oninput: move |event| {
+                        if asking_password {
+                            password.set(event.value());
+                        } else if let Some(current) = search() {
+                            // Keep the current match while it still contains the query
+                            let from = current.found.map_or(usize::MAX, |idx| idx + 1);
+                            let found = state.read().history().search(&event.value(), from);
+                            search.set(Some(Search { query: event.value(), found, ..current }));
+                        } else {
+                            command.set(event.value())
+                        }
+                    },
                     onkeydown: move |event| {
+                        if event.data.key() != Key::Tab {
+                            tab_pressed.set(false);
+                            completions.set(vec![]);
+                        }
+                        let ctrl = event.data.modifiers().ctrl();
+                        if asking_password {
+                            // The password is never shown, completed or kept in the history
+                            match event.data.key() {
+                                Key::Enter => su::authenticate(&password()),
+                                Key::Escape => su::cancel(),
+                                Key::Character(chr) if ctrl && chr == "c" => su::cancel(),
+                                _ => return,
+                            }
+                            password.set("".to_string());
+                            return;
+                        }
+                        if let Some(current) = search() {
+                            match event.data.key() {
+                                Key::Character(chr) if ctrl && chr == "r" => {
+                                    let from = current.found.unwrap_or(usize::MAX);
+                                    let found = state.read().history().search(&current.query, from);
+                                    if let Some(found) = found {
+                                        search.set(Some(Search { found: Some(found), ..current }));
+                                    }
+                                }
+                                Key::Character(chr) if ctrl && chr == "g" => {
+                                    command.set(current.original);
+                                    search.set(None);
+                                }
+                                Key::Escape => {
+                                    command.set(current.original);
+                                    search.set(None);
+                                }
+                                Key::Enter => {
+                                    search.set(None);
+                                    let line = current
+                                        .found
+                                        .and_then(|idx| state.read().history().entries().get(idx).cloned())
+                                        .unwrap_or(current.original);
+                                    submit(line);
+                                }
+                                _ => {}
+                            }
+                            return;
+                        }
                         match event.data.key() {
-                            Key::Enter => {
-                                crate::terminal::commands::exec(command());
-                                state.write().history_mut().push(command());
-                                state.write().update_history();
-                                command.set("".to_string());
-                                typed.set("".to_string());
-                                hidden_buf.set("".to_string());
+                            Key::Enter => submit(command()),
+                            Key::Character(chr) if ctrl && chr == "r" => {
+                                search.set(Some(Search { original: command(), ..Default::default() }));
                             }
                             Key::Tab => {
                                 eval("document.getElementById('input').focus()");
+                                let completion = {
+                                    let state = state.read();
+                                    completion::complete(&command(), state.fs(), state.env(), state.aliases())
+                                };
+                                if completion.line != command() {
+                                    command.set(completion.line.clone());
+                                    hidden_buf.set(completion.line);
+                                } else if tab_pressed() {
+                                    completions.set(completion.candidates);
+                                }
+                                tab_pressed.set(true);
+                            }
+                            Key::ArrowRight | Key::End if suggestion.is_some() && caret_at_end() => {
+                                let suggestion = suggestion.clone().unwrap_or_default();
+                                if event.data.modifiers().alt() {
+                                    command.set(command() + next_word(&suggestion[command().len()..]));
+                                } else {
+                                    command.set(suggestion);
+                                }
+                                hidden_buf.set(command());
                             }
                             Key::ArrowUp => {
                                 if let Some(cmd) = state.write().history_mut().nav_back() {
@@ -55,10 +195,8 @@ pub(crate) fn Prompt() -> Element {
                                     command.set(typed());
                                 }
                             }
-                            Key::Character(chr) => {
-                                if typed().is_empty() {
-                                    hidden_buf.set(hidden_buf() + &chr);
-                                }
+                            Key::Character(chr) if typed().is_empty() => {
+                                hidden_buf.set(hidden_buf() + &chr);
                             }
                             _ => {}
                         }
@@ -66,32 +204,97 @@ pub(crate) fn Prompt() -> Element {
                 }
             }
         }
+        if let Some((entry, query)) = found {
+            div { id: "search-match",
+                HighlightedMatch { entry, query }
+            }
+        } else if search().is_some_and(|search| !search.query.is_empty()) {
+            div { id: "search-match", "failing reverse-i-search" }
+        }
+        if !completions().is_empty() {
+            div { id: "completions",
+                for candidate in completions() {
+                    span { "{candidate}" }
+                }
+            }
+        }
     }
 }
 
+/// Whether the caret of the input is after its last character, with nothing selected.
+/// Otherwise the arrow keys move it as usual instead of accepting the suggestion.
+fn caret_at_end() -> bool {
+    let Some(input) = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id("input"))
+        .and_then(|input| input.dyn_into::<HtmlInputElement>().ok())
+    else {
+        return true;
+    };
+    // The positions count UTF-16 code units, like the length of the value in JavaScript
+    let len = input.value().encode_utf16().count() as u32;
+    matches!(
+        (input.selection_start(), input.selection_end()),
+        (Ok(Some(start)), Ok(Some(end))) if start == len && end == len
+    )
+}
+
+/// The start of `text` up to the end of its first word
+fn next_word(text: &str) -> &str {
+    let start = text.len() - text.trim_start().len();
+    let end = text[start..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |end| start + end);
+    &text[..end]
+}
+
+/// A history entry with the last occurrence of `query` highlighted
 #[component]
-pub(crate) fn PromptText(command_failed: bool) -> Element {
+fn HighlightedMatch(entry: String, query: String) -> Element {
+    let theme = *consume_context::<Signal<AppState>>().read().theme();
+    let (before, matched, after) = match entry.rfind(&query).filter(|_| !query.is_empty()) {
+        Some(idx) => (
+            &entry[..idx],
+            &entry[idx..idx + query.len()],
+            &entry[idx + query.len()..],
+        ),
+        None => (entry.as_str(), "", ""),
+    };
+    rsx! {
+        span { "{before}" }
+        span {
+            style: "color: {theme.crust.hex()}; background-color: {theme.peach.hex()}",
+            "{matched}"
+        }
+        span { "{after}" }
+    }
+}
+
+#[component]
+pub(crate) fn PromptText(status: i32) -> Element {
     let state = consume_context::<Signal<AppState>>();
-    let dollar_color = if !command_failed {
-        state().theme().green.hex()
+    let state = state.read();
+    let theme = state.theme();
+    let dollar_color = if status == 0 {
+        theme.green.hex()
     } else {
-        state().theme().red.hex()
+        theme.red.hex()
     };
     rsx! {
-        span { style: "color: {state().theme().sapphire.hex()}", "user@aureliobuonomo.it" }
+        span { style: "color: {theme.sapphire.hex()}", "{state.fs().user()}@{HOSTNAME}" }
         span { style: "white-space: pre", ": " }
-        span { style: "color: {state().theme().red.hex()}", {state().fs().cwd()} }
+        span { style: "color: {theme.red.hex()}", {state.fs().cwd()} }
         span { style: "white-space: pre; color: {dollar_color}", " ❯ " }
     }
 }
 
 #[component]
-pub(crate) fn SimplePromptText(command_failed: bool) -> Element {
-    let state = consume_context::<Signal<AppState>>();
-    let dollar_color = if !command_failed {
-        state().theme().green.hex()
+pub(crate) fn SimplePromptText(status: i32) -> Element {
+    let theme = *consume_context::<Signal<AppState>>().read().theme();
+    let dollar_color = if status == 0 {
+        theme.green.hex()
     } else {
-        state().theme().red.hex()
+        theme.red.hex()
     };
     rsx! {
         span { style: "white-space: pre; color: {dollar_color}", "❯ " }